@@ -0,0 +1,86 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use lcrs::terms::{self, ChurchNumeral};
+use lcrs::{DeBrujin, Expr, Strategy};
+
+// `λy. (λx. x) ((λx. x) (... y))`, `depth` redexes deep. Closed, since
+// `debrujin` rejects free variables.
+fn identity_chain(depth: usize) -> Expr {
+    let id = Expr::lambda("x", Expr::var("x"));
+    Expr::lambda(
+        "y",
+        (0..depth).fold(Expr::var("y"), |acc, _| id.apply(&acc)),
+    )
+}
+
+fn workloads() -> Vec<(&'static str, Expr)> {
+    vec![
+        ("add 10 10", terms::add().call(&[10_u32, 10])),
+        ("mul 8 8", terms::mul().call(&[8_u32, 8])),
+        ("exp 2 10", terms::exp().call(&[2_u32, 10])),
+        (
+            "mul (mul 2 2) 3",
+            terms::mul().apply_all(&[terms::mul().call(&[2_u32, 2]), 3_u32.to_church()]),
+        ),
+        ("identity chain 200", identity_chain(200)),
+    ]
+}
+
+// Every normalizer on every workload, grouped by workload so the reducers
+// can be compared side by side.
+fn normalizers(c: &mut Criterion) {
+    for (name, term) in workloads() {
+        let mut group = c.benchmark_group(name);
+        group.sample_size(10);
+        group.bench_function("full_reduction", |b| b.iter(|| term.full_reduction()));
+        group.bench_function("normalize_nbe", |b| b.iter(|| term.normalize_nbe()));
+        group.bench_function("normalize_shared", |b| b.iter(|| term.normalize_shared()));
+        group.bench_function("debrujin normalize", |b| {
+            b.iter(|| term.debrujin().normalize())
+        });
+        for strategy in Strategy::ALL {
+            group.bench_with_input(
+                BenchmarkId::new("reduce_with", format!("{strategy:?}")),
+                &strategy,
+                |b, &s| b.iter(|| term.reduce_with(s, usize::MAX)),
+            );
+        }
+        group.finish();
+    }
+}
+
+// The conversion before it stopped copying the context at every lambda,
+// kept to compare against.
+fn debrujin_cloning(e: &Expr, ctx: &Vec<String>) -> DeBrujin {
+    match e {
+        Expr::Lam(id, body) => {
+            let mut ctx = ctx.clone();
+            ctx.push(id.clone());
+            DeBrujin::Lam(Box::new(debrujin_cloning(body, &ctx)))
+        }
+        Expr::App(m, n) => DeBrujin::App(
+            Box::new(debrujin_cloning(m, ctx)),
+            Box::new(debrujin_cloning(n, ctx)),
+        ),
+        Expr::Var(id) => DeBrujin::Var(ctx.iter().rev().position(|x| x == id).unwrap() as u32),
+    }
+}
+
+// `λx0 x1 ... x999. x0`.
+fn deep_lambda(depth: usize) -> Expr {
+    (0..depth).rev().fold(Expr::var("x0"), |acc, i| {
+        Expr::lambda(&format!("x{i}"), acc)
+    })
+}
+
+fn debrujin(c: &mut Criterion) {
+    let term = deep_lambda(1000);
+    let mut group = c.benchmark_group("debrujin 1000 binders");
+    group.bench_function("debrujin", |b| b.iter(|| term.debrujin()));
+    group.bench_function("cloning context", |b| {
+        b.iter(|| debrujin_cloning(&term, &vec![]))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, normalizers, debrujin);
+criterion_main!(benches);
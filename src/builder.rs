@@ -0,0 +1,133 @@
+// A fluent way to write programs over the `terms` encodings, mostly for
+// tests: `term().num(5).add(term().num(7)).build()` is `add 5 7`, with
+// `terms::add()` and Church numerals in place. Nothing is reduced.
+//
+// A builder starts empty. `num`, `bool`, `list` and `expr` make it that
+// value, or, once it has one, apply it to the value, so
+// `term().expr(terms::add()).num(5).num(7)` is the same `add 5 7`. The
+// operations apply the matching `terms` function to the term built so far
+// and their arguments, in the order that function takes them.
+
+use super::*;
+
+#[derive(Clone, Debug, Default)]
+pub struct Builder {
+    expr: Option<Expr>,
+}
+
+pub fn term() -> Builder {
+    Builder::default()
+}
+
+// `add`, `not` and the rest build the Church operations of those names;
+// they are not arithmetic on builders, so `Builder` does not implement
+// `core::ops`.
+#[allow(clippy::should_implement_trait)]
+impl Builder {
+    // The term built so far. Panics on an empty builder.
+    pub fn build(self) -> Expr {
+        self.expr.expect("build: the builder is empty")
+    }
+
+    pub fn expr(self, e: Expr) -> Builder {
+        let expr = match self.expr {
+            Some(head) => head.apply(&e),
+            None => e,
+        };
+        Builder { expr: Some(expr) }
+    }
+
+    pub fn num(self, n: u32) -> Builder {
+        self.expr(terms::nat(n))
+    }
+
+    pub fn bool(self, b: bool) -> Builder {
+        self.expr(terms::ToLambda::to_lambda(&b))
+    }
+
+    // `cons x1 (cons x2 ... nil)`, unreduced.
+    pub fn list(self, items: impl IntoIterator<Item = Builder>) -> Builder {
+        let items: Vec<Expr> = items.into_iter().map(Builder::build).collect();
+        let list = items.into_iter().rev().fold(terms::nil(), |tail, head| {
+            terms::cons().apply(&head).apply(&tail)
+        });
+        self.expr(list)
+    }
+
+    // `head self args...`.
+    fn op(self, head: Expr, args: impl IntoIterator<Item = Builder>) -> Builder {
+        let args = core::iter::once(self).chain(args).map(Builder::build);
+        term().expr(Expr::app_spine(head, args))
+    }
+
+    pub fn succ(self) -> Builder {
+        self.op(terms::succ(), [])
+    }
+
+    pub fn pred(self) -> Builder {
+        self.op(terms::pred(), [])
+    }
+
+    pub fn add(self, other: Builder) -> Builder {
+        self.op(terms::add(), [other])
+    }
+
+    pub fn sub(self, other: Builder) -> Builder {
+        self.op(terms::sub(), [other])
+    }
+
+    pub fn mul(self, other: Builder) -> Builder {
+        self.op(terms::mul(), [other])
+    }
+
+    pub fn exp(self, other: Builder) -> Builder {
+        self.op(terms::exp(), [other])
+    }
+
+    pub fn is_zero(self) -> Builder {
+        self.op(terms::is_zero(), [])
+    }
+
+    pub fn leq(self, other: Builder) -> Builder {
+        self.op(terms::leq(), [other])
+    }
+
+    pub fn eq_nat(self, other: Builder) -> Builder {
+        self.op(terms::eq_nat(), [other])
+    }
+
+    pub fn not(self) -> Builder {
+        self.op(terms::not(), [])
+    }
+
+    pub fn and(self, other: Builder) -> Builder {
+        self.op(terms::and(), [other])
+    }
+
+    pub fn or(self, other: Builder) -> Builder {
+        self.op(terms::or(), [other])
+    }
+
+    // `ite self then otherwise`.
+    pub fn ite(self, then: Builder, otherwise: Builder) -> Builder {
+        self.op(terms::ite(), [then, otherwise])
+    }
+
+    pub fn length(self) -> Builder {
+        self.op(terms::length(), [])
+    }
+
+    pub fn append(self, other: Builder) -> Builder {
+        self.op(terms::append(), [other])
+    }
+
+    // `map f self`: here the list comes second.
+    pub fn map(self, f: Builder) -> Builder {
+        f.op(terms::map(), [self])
+    }
+
+    // `filter p self`.
+    pub fn filter(self, p: Builder) -> Builder {
+        p.op(terms::filter(), [self])
+    }
+}
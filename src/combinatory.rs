@@ -0,0 +1,117 @@
+// Combinatory logic: terms built from `S`, `K` and `I` by application, with
+// no binders. `to_ski` compiles a lambda term into one by bracket
+// abstraction, and `to_lambda` goes back by expanding each combinator.
+
+use super::terms::combinators;
+use super::*;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CL {
+    S,
+    K,
+    I,
+    App(Box<CL>, Box<CL>),
+    Var(Id),
+}
+
+impl fmt::Display for CL {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CL::S => write!(f, "S"),
+            CL::K => write!(f, "K"),
+            CL::I => write!(f, "I"),
+            CL::App(m, n) => match n.as_ref() {
+                CL::App(..) => write!(f, "{} ({})", m, n),
+                _ => write!(f, "{} {}", m, n),
+            },
+            CL::Var(id) => write!(f, "{}", id),
+        }
+    }
+}
+
+impl CL {
+    pub fn app(self, arg: CL) -> CL {
+        CL::App(Box::new(self), Box::new(arg))
+    }
+
+    // One leftmost-outermost step of `I x → x`, `K x y → x` and
+    // `S f g x → f x (g x)`; `None` in normal form.
+    pub fn reduce(&self) -> Option<CL> {
+        let mut head = self;
+        let mut args = vec![];
+        while let CL::App(m, n) = head {
+            args.push(n.as_ref());
+            head = m;
+        }
+        args.reverse();
+        let (contracted, used) = match (head, args.as_slice()) {
+            (CL::I, [x, ..]) => ((*x).clone(), 1),
+            (CL::K, [x, _, ..]) => ((*x).clone(), 2),
+            (CL::S, [f, g, x, ..]) => {
+                let fx = (*f).clone().app((*x).clone());
+                (fx.app((*g).clone().app((*x).clone())), 3)
+            }
+            _ => {
+                let (i, next) = args
+                    .iter()
+                    .enumerate()
+                    .find_map(|(i, arg)| Some((i, arg.reduce()?)))?;
+                let mut args: Vec<CL> = args.into_iter().cloned().collect();
+                args[i] = next;
+                return Some(args.into_iter().fold(head.clone(), CL::app));
+            }
+        };
+        Some(
+            args[used..]
+                .iter()
+                .fold(contracted, |t, a| t.app((*a).clone())),
+        )
+    }
+
+    pub fn normalize(&self) -> CL {
+        let mut term = self.clone();
+        while let Some(next) = term.reduce() {
+            term = next;
+        }
+        term
+    }
+
+    pub fn to_lambda(&self) -> Expr {
+        match self {
+            CL::S => combinators::s(),
+            CL::K => combinators::k(),
+            CL::I => combinators::i(),
+            CL::App(m, n) => m.to_lambda().apply(&n.to_lambda()),
+            CL::Var(id) => Expr::Var(id.clone()),
+        }
+    }
+
+    fn occurs(&self, x: &Id) -> bool {
+        match self {
+            CL::App(m, n) => m.occurs(x) || n.occurs(x),
+            CL::Var(id) => id == x,
+            _ => false,
+        }
+    }
+
+    // `[x] self`, a term that applied to `a` reduces to `self[x := a]`.
+    // There is no eta rule, so `to_lambda` of the result is beta-equal to
+    // `λx. self` and keeps its beta normal form.
+    fn abstract_over(&self, x: &Id) -> CL {
+        match self {
+            CL::Var(id) if id == x => CL::I,
+            _ if !self.occurs(x) => CL::K.app(self.clone()),
+            CL::App(m, n) => CL::S.app(m.abstract_over(x)).app(n.abstract_over(x)),
+            _ => unreachable!("only variables and applications mention `x`"),
+        }
+    }
+}
+
+// Free variables stay variables, so `to_ski` works on open terms too.
+pub fn to_ski(e: &Expr) -> CL {
+    match e {
+        Expr::Lam(id, body) => to_ski(body).abstract_over(id),
+        Expr::App(m, n) => to_ski(m).app(to_ski(n)),
+        Expr::Var(id) => CL::Var(id.clone()),
+    }
+}
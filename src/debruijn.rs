@@ -0,0 +1,214 @@
+use super::*;
+
+// Variables are de Bruijn indices: `0` is the innermost enclosing binder.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DeBrujin {
+    Lam(Box<DeBrujin>),
+    App(Box<DeBrujin>, Box<DeBrujin>),
+    Var(u32),
+}
+
+// Variables are de Bruijn levels: `0` is the outermost enclosing binder.
+// Indices count from the variable out, levels from the root in, so the same
+// variable keeps its level wherever it occurs under more binders, e.g.
+// `λx. x (λy. x)` is `λ. 0 (λ. 1)` in indices and `λ. 0 (λ. 0)` in levels.
+// Like indices, levels are equal exactly for alpha-equivalent terms.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DeBrujinLevel {
+    Lam(Box<DeBrujinLevel>),
+    App(Box<DeBrujinLevel>, Box<DeBrujinLevel>),
+    Var(u32),
+}
+
+impl DeBrujinLevel {
+    pub fn to_indices(&self) -> DeBrujin {
+        self.to_indices_at(0)
+    }
+
+    fn to_indices_at(&self, depth: u32) -> DeBrujin {
+        match self {
+            DeBrujinLevel::Lam(body) => DeBrujin::Lam(Box::new(body.to_indices_at(depth + 1))),
+            DeBrujinLevel::App(m, n) => DeBrujin::App(
+                Box::new(m.to_indices_at(depth)),
+                Box::new(n.to_indices_at(depth)),
+            ),
+            DeBrujinLevel::Var(level) => DeBrujin::Var(depth - 1 - level),
+        }
+    }
+}
+
+impl DeBrujin {
+    // Panics on indices that point past the root, as `to_named` does.
+    pub fn to_levels(&self) -> DeBrujinLevel {
+        self.to_levels_at(0)
+    }
+
+    fn to_levels_at(&self, depth: u32) -> DeBrujinLevel {
+        match self {
+            DeBrujin::Lam(body) => DeBrujinLevel::Lam(Box::new(body.to_levels_at(depth + 1))),
+            DeBrujin::App(m, n) => DeBrujinLevel::App(
+                Box::new(m.to_levels_at(depth)),
+                Box::new(n.to_levels_at(depth)),
+            ),
+            DeBrujin::Var(index) => DeBrujinLevel::Var(depth - 1 - index),
+        }
+    }
+}
+
+impl DeBrujin {
+    // Names the binder at depth `d` as `x{d}`; the inverse of `debrujin` up
+    // to alpha-equivalence.
+    pub fn to_named(&self) -> Expr {
+        self.to_named_at(0)
+    }
+
+    fn to_named_at(&self, depth: u32) -> Expr {
+        match self {
+            DeBrujin::Lam(body) => {
+                Expr::Lam(format!("x{depth}"), Box::new(body.to_named_at(depth + 1)))
+            }
+            DeBrujin::App(m, n) => Expr::App(
+                Box::new(m.to_named_at(depth)),
+                Box::new(n.to_named_at(depth)),
+            ),
+            DeBrujin::Var(index) => Expr::Var(format!("x{}", depth - 1 - index)),
+        }
+    }
+
+    // Adds `d` to every index at or above `cutoff`, i.e. to the variables
+    // bound outside the `cutoff` innermost binders. Panics if a negative `d`
+    // would move one of them below `cutoff`, where it would be captured.
+    pub fn shift(&self, d: i32, cutoff: u32) -> DeBrujin {
+        match self {
+            DeBrujin::Lam(body) => DeBrujin::Lam(Box::new(body.shift(d, cutoff + 1))),
+            DeBrujin::App(m, n) => {
+                DeBrujin::App(Box::new(m.shift(d, cutoff)), Box::new(n.shift(d, cutoff)))
+            }
+            DeBrujin::Var(k) if *k >= cutoff => match k.checked_add_signed(d) {
+                Some(shifted) if shifted >= cutoff => DeBrujin::Var(shifted),
+                _ => panic!("shift: index {k} shifted by {d} falls below cutoff {cutoff}"),
+            },
+            DeBrujin::Var(_) => self.clone(),
+        }
+    }
+
+    // `self[j := s]`, with `s` shifted as it goes under binders.
+    pub fn subst(&self, j: u32, s: &DeBrujin) -> DeBrujin {
+        match self {
+            DeBrujin::Lam(body) => DeBrujin::Lam(Box::new(body.subst(j + 1, &s.shift(1, 0)))),
+            DeBrujin::App(m, n) => DeBrujin::App(Box::new(m.subst(j, s)), Box::new(n.subst(j, s))),
+            DeBrujin::Var(k) if *k == j => s.clone(),
+            DeBrujin::Var(_) => self.clone(),
+        }
+    }
+
+    // One normal-order (leftmost-outermost) contraction, or `None` in normal
+    // form. Works on indices directly, so no names are ever generated.
+    pub fn reduce(&self) -> Option<DeBrujin> {
+        match self {
+            DeBrujin::App(m, n) => match m.as_ref() {
+                DeBrujin::Lam(body) => Some(body.subst(0, &n.shift(1, 0)).shift(-1, 0)),
+                _ => match m.reduce() {
+                    Some(m) => Some(DeBrujin::App(Box::new(m), n.clone())),
+                    None => Some(DeBrujin::App(m.clone(), Box::new(n.reduce()?))),
+                },
+            },
+            DeBrujin::Lam(body) => Some(DeBrujin::Lam(Box::new(body.reduce()?))),
+            DeBrujin::Var(_) => None,
+        }
+    }
+
+    // Whether `e` has this de Bruijn form, found without building it. Free
+    // variables of `e` match nothing, where `debrujin` would panic.
+    pub(crate) fn is_form_of(&self, e: &Expr) -> bool {
+        self.is_form_of_with(e, &mut vec![])
+    }
+
+    fn is_form_of_with<'a>(&self, e: &'a Expr, ctx: &mut Vec<&'a Id>) -> bool {
+        match (self, e) {
+            (DeBrujin::Lam(body1), Expr::Lam(id, body2)) => {
+                ctx.push(id);
+                let eq = body1.is_form_of_with(body2, ctx);
+                ctx.pop();
+                eq
+            }
+            (DeBrujin::App(m1, n1), Expr::App(m2, n2)) => {
+                m1.is_form_of_with(m2, ctx) && n1.is_form_of_with(n2, ctx)
+            }
+            (DeBrujin::Var(index), Expr::Var(id)) => {
+                ctx.iter().rev().position(|x| *x == id) == Some(*index as usize)
+            }
+            _ => false,
+        }
+    }
+
+    pub fn normalize(&self) -> DeBrujin {
+        let mut term = self.clone();
+        while let Some(next) = term.reduce() {
+            term = next;
+        }
+        term
+    }
+}
+
+impl Expr {
+    pub fn debrujin(&self) -> DeBrujin {
+        self.debrujin_with(&mut vec![])
+    }
+
+    // `ctx` holds the enclosing binders, innermost last. A lambda pushes its
+    // binder for the body and pops it afterwards, so nothing is copied.
+    fn debrujin_with<'a>(&'a self, ctx: &mut Vec<&'a Id>) -> DeBrujin {
+        match self {
+            Expr::Lam(id, expr) => {
+                ctx.push(id);
+                let body = expr.debrujin_with(ctx);
+                ctx.pop();
+                DeBrujin::Lam(Box::new(body))
+            }
+            Expr::App(expr1, expr2) => DeBrujin::App(
+                Box::new(expr1.debrujin_with(ctx)),
+                Box::new(expr2.debrujin_with(ctx)),
+            ),
+            Expr::Var(id) => {
+                if let Some(index) = ctx.iter().rev().position(|x| *x == id) {
+                    DeBrujin::Var(index as u32)
+                } else {
+                    panic!("Unbound variable {}", id)
+                }
+            }
+        }
+    }
+
+    // Panics on free variables, like `debrujin`.
+    pub fn debruijn_levels(&self) -> DeBrujinLevel {
+        self.debruijn_levels_with(&mut vec![])
+    }
+
+    // `ctx` holds the enclosing binders, outermost first, so a variable's
+    // level is the position of its innermost binder.
+    fn debruijn_levels_with<'a>(&'a self, ctx: &mut Vec<&'a Id>) -> DeBrujinLevel {
+        match self {
+            Expr::Lam(id, body) => {
+                ctx.push(id);
+                let body = body.debruijn_levels_with(ctx);
+                ctx.pop();
+                DeBrujinLevel::Lam(Box::new(body))
+            }
+            Expr::App(m, n) => DeBrujinLevel::App(
+                Box::new(m.debruijn_levels_with(ctx)),
+                Box::new(n.debruijn_levels_with(ctx)),
+            ),
+            Expr::Var(id) => match ctx.iter().rposition(|x| *x == id) {
+                Some(level) => DeBrujinLevel::Var(level as u32),
+                None => panic!("Unbound variable {}", id),
+            },
+        }
+    }
+
+    pub fn exact_equivalence(&self, other: &Expr) -> bool {
+        self.debrujin() == other.debrujin()
+    }
+}
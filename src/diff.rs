@@ -0,0 +1,106 @@
+use super::*;
+
+// One place where two terms differ, with its path from the root of both.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DiffNode {
+    // Both sides are lambdas binding different names. Their bodies are
+    // compared as well.
+    Binder { path: Path, left: Id, right: Id },
+    // Different variables, or a lambda against a variable.
+    Head { path: Path, left: Expr, right: Expr },
+    // An application on the left where the right has none, as in `λx. x x`
+    // against `λx. x`.
+    LeftOnly { path: Path, subtree: Expr },
+    RightOnly { path: Path, subtree: Expr },
+}
+
+// Where two terms first stop being alpha-equivalent. `path` leads to the
+// same node in both, and in their de Bruijn forms.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mismatch {
+    pub path: Path,
+    // The two subterms at `path`, e.g. "`x` against `x x`".
+    pub description: String,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "at {:?}: {}", self.path, self.description)
+    }
+}
+
+impl Expr {
+    // Walks both terms in parallel and reports every node where they part,
+    // outermost first and left to right. Names matter: `λx. x` and `λy. y`
+    // differ in their binders and variables. Empty exactly when the terms
+    // are equal.
+    pub fn diff(&self, other: &Expr) -> Vec<DiffNode> {
+        let mut diffs = vec![];
+        self.diff_with(other, &mut vec![], &mut diffs);
+        diffs
+    }
+
+    // `diff` of the canonical forms, so only differences that survive
+    // alpha-renaming are reported. Empty exactly when `alpha_eq` holds.
+    pub fn alpha_diff(&self, other: &Expr) -> Vec<DiffNode> {
+        self.to_canonical().diff(&other.to_canonical())
+    }
+
+    // `alpha_eq` that on failure says where: the first entry of
+    // `alpha_diff`, described with the original names.
+    pub fn alpha_eq_explain(&self, other: &Expr) -> Result<(), Mismatch> {
+        let Some(first) = self.alpha_diff(other).into_iter().next() else {
+            return Ok(());
+        };
+        let path = match first {
+            DiffNode::Binder { path, .. }
+            | DiffNode::Head { path, .. }
+            | DiffNode::LeftOnly { path, .. }
+            | DiffNode::RightOnly { path, .. } => path,
+        };
+        let at = |e: &Expr| e.subterm(&path).unwrap().to_string();
+        Err(Mismatch {
+            description: format!("`{}` against `{}`", at(self), at(other)),
+            path,
+        })
+    }
+
+    fn diff_with(&self, other: &Expr, path: &mut Path, diffs: &mut Vec<DiffNode>) {
+        match (self, other) {
+            (Expr::Lam(x, m), Expr::Lam(y, n)) => {
+                if x != y {
+                    diffs.push(DiffNode::Binder {
+                        path: path.clone(),
+                        left: x.clone(),
+                        right: y.clone(),
+                    });
+                }
+                path.push(Dir::LamBody);
+                m.diff_with(n, path, diffs);
+                path.pop();
+            }
+            (Expr::App(m1, n1), Expr::App(m2, n2)) => {
+                path.push(Dir::AppLeft);
+                m1.diff_with(m2, path, diffs);
+                path.pop();
+                path.push(Dir::AppRight);
+                n1.diff_with(n2, path, diffs);
+                path.pop();
+            }
+            (Expr::Var(x), Expr::Var(y)) if x == y => {}
+            (Expr::App(..), _) => diffs.push(DiffNode::LeftOnly {
+                path: path.clone(),
+                subtree: self.clone(),
+            }),
+            (_, Expr::App(..)) => diffs.push(DiffNode::RightOnly {
+                path: path.clone(),
+                subtree: other.clone(),
+            }),
+            _ => diffs.push(DiffNode::Head {
+                path: path.clone(),
+                left: self.clone(),
+                right: other.clone(),
+            }),
+        }
+    }
+}
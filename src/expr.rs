@@ -0,0 +1,800 @@
+use super::*;
+
+// The name a binder `id` is renamed to when it would capture: the first of
+// `id'`, `id''`, ... not in `avoid`. Depends only on its arguments, so
+// reduction gives the same names on every run and every thread.
+pub(crate) fn freshen(id: &str, avoid: &[Id]) -> Id {
+    let mut candidate = format!("{id}'");
+    while avoid.contains(&candidate) {
+        candidate.push('\'');
+    }
+    candidate
+}
+
+// FNV-1a, for `fingerprint`: deterministic, and available without `std`.
+struct Fnv(u64);
+
+impl Default for Fnv {
+    fn default() -> Fnv {
+        Fnv(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl core::hash::Hasher for Fnv {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+pub type Id = String;
+
+// A source of the names `v1`, `v2`, ... for renamed binders, as an
+// alternative to `freshen`'s primes. Each evaluation owns its supply, so the
+// names only depend on the term and the supply.
+#[derive(Debug, Default)]
+pub struct FreshSupply {
+    next: u32,
+}
+
+impl FreshSupply {
+    pub fn new() -> FreshSupply {
+        FreshSupply::default()
+    }
+
+    pub fn fresh(&mut self) -> Id {
+        self.next += 1;
+        format!("v{}", self.next)
+    }
+}
+
+// Named definitions, referred to by free variables of a term.
+#[cfg(feature = "std")]
+pub type Env = std::collections::HashMap<Id, Expr>;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Expr {
+    Lam(Id, Box<Expr>),
+    App(Box<Expr>, Box<Expr>),
+    Var(Id),
+}
+
+// The identity, `λx. x`.
+impl Default for Expr {
+    fn default() -> Expr {
+        Expr::lambda("x", Expr::var("x"))
+    }
+}
+
+// A term compared up to alpha: `==` is `alpha_eq` and the hash is
+// `fingerprint`, so `AlphaEq(λx. x) == AlphaEq(λy. y)` and a set of
+// `AlphaEq` keeps one term per class. `Expr`'s own `==` stays syntactic,
+// binder names included, since the renaming in substitution and the tests
+// of it need to see exactly which names came out.
+#[derive(Clone, Debug)]
+pub struct AlphaEq(pub Expr);
+
+impl PartialEq for AlphaEq {
+    fn eq(&self, other: &AlphaEq) -> bool {
+        self.0.alpha_eq(&other.0)
+    }
+}
+
+impl Eq for AlphaEq {}
+
+impl core::hash::Hash for AlphaEq {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.0.fingerprint().hash(state);
+    }
+}
+
+impl From<Expr> for AlphaEq {
+    fn from(e: Expr) -> AlphaEq {
+        AlphaEq(e)
+    }
+}
+
+impl fmt::Display for AlphaEq {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Dir {
+    LamBody,
+    AppLeft,
+    AppRight,
+}
+
+pub type Path = Vec<Dir>;
+
+pub(crate) type Fresh<'a> = dyn FnMut(&str, &[Id]) -> Id + 'a;
+
+// Work items for the iterative traversals that rebuild a term: `Visit`
+// schedules a subterm, `Lam` and `App` reassemble a node from the results of
+// its children, which are on top of the result stack.
+pub(crate) enum Build<'a> {
+    Visit(&'a Expr),
+    Lam(Id),
+    App,
+}
+
+impl Expr {
+    // Free occurrences from left to right, duplicates included.
+    pub fn fv(&self) -> Vec<Id> {
+        let mut fv = vec![];
+        let mut scope = vec![];
+        // `None` leaves the innermost binder once its body has been walked.
+        let mut todo = vec![Some(self)];
+        while let Some(item) = todo.pop() {
+            match item {
+                Some(Expr::Lam(id, body)) => {
+                    scope.push(id);
+                    todo.push(None);
+                    todo.push(Some(body));
+                }
+                Some(Expr::App(m, n)) => {
+                    todo.push(Some(n));
+                    todo.push(Some(m));
+                }
+                Some(Expr::Var(id)) if !scope.contains(&id) => fv.push(id.clone()),
+                Some(Expr::Var(_)) => {}
+                None => {
+                    scope.pop();
+                }
+            }
+        }
+        fv
+    }
+
+    pub fn occurs_free(&self, id: &str) -> bool {
+        match self {
+            Expr::Lam(x, _) if x == id => false,
+            Expr::Lam(_, body) => body.occurs_free(id),
+            Expr::App(m, n) => m.occurs_free(id) || n.occurs_free(id),
+            Expr::Var(x) => x == id,
+        }
+    }
+
+    // Occurrences of `id` not shadowed by an inner binder.
+    pub fn free_occurrence_count(&self, id: &str) -> usize {
+        match self {
+            Expr::Lam(x, _) if x == id => 0,
+            Expr::Lam(_, body) => body.free_occurrence_count(id),
+            Expr::App(m, n) => m.free_occurrence_count(id) + n.free_occurrence_count(id),
+            Expr::Var(x) => usize::from(x == id),
+        }
+    }
+
+    // Where the occurrences `free_occurrence_count` counts are, left to
+    // right, e.g. to contract around them with `beta_reduce_at`.
+    pub fn free_var_paths(&self, id: &str) -> Vec<Path> {
+        self.find_subterm(&Expr::var(id))
+    }
+
+    // Names bound by some lambda in the term, in order of first appearance.
+    pub fn bound_variables(&self) -> Vec<Id> {
+        let mut ids = vec![];
+        self.collect_variables(&mut ids, false);
+        ids
+    }
+
+    // Every name used in the term, bound or free, in order of first appearance.
+    pub fn all_variables(&self) -> Vec<Id> {
+        let mut ids = vec![];
+        self.collect_variables(&mut ids, true);
+        ids
+    }
+
+    fn collect_variables(&self, ids: &mut Vec<Id>, with_occurrences: bool) {
+        match self {
+            Expr::Lam(id, expr) => {
+                if !ids.contains(id) {
+                    ids.push(id.clone());
+                }
+                expr.collect_variables(ids, with_occurrences);
+            }
+            Expr::App(expr1, expr2) => {
+                expr1.collect_variables(ids, with_occurrences);
+                expr2.collect_variables(ids, with_occurrences);
+            }
+            Expr::Var(id) => {
+                if with_occurrences && !ids.contains(id) {
+                    ids.push(id.clone());
+                }
+            }
+        }
+    }
+
+    // Number of lambdas, applications and variables.
+    pub fn node_counts(&self) -> (usize, usize, usize) {
+        match self {
+            Expr::Lam(_, body) => {
+                let (l, a, v) = body.node_counts();
+                (l + 1, a, v)
+            }
+            Expr::App(m, n) => {
+                let (l1, a1, v1) = m.node_counts();
+                let (l2, a2, v2) = n.node_counts();
+                (l1 + l2, a1 + a2 + 1, v1 + v2)
+            }
+            Expr::Var(_) => (0, 0, 1),
+        }
+    }
+
+    // Number of nodes.
+    pub fn size(&self) -> usize {
+        match self {
+            Expr::Lam(_, body) => 1 + body.size(),
+            Expr::App(m, n) => 1 + m.size() + n.size(),
+            Expr::Var(_) => 1,
+        }
+    }
+
+    // Number of nodes on the longest path from the root to a variable.
+    pub fn depth(&self) -> usize {
+        self.cata(|_, body| 1 + body, |m, n: usize| 1 + m.max(n), |_| 1)
+    }
+
+    // Alpha-invariant hash of the node counts and the first few levels of the
+    // tree. Alpha-equivalent terms always agree; a mismatch proves the terms
+    // differ without a full comparison.
+    pub fn fingerprint(&self) -> u64 {
+        use core::hash::{Hash, Hasher};
+        let mut hasher = Fnv::default();
+        self.node_counts().hash(&mut hasher);
+        self.digest(4, &mut vec![], &mut hasher);
+        hasher.finish()
+    }
+
+    fn digest(&self, depth: usize, scope: &mut Vec<Id>, hasher: &mut impl core::hash::Hasher) {
+        use core::hash::Hash;
+        match self {
+            Expr::Lam(id, body) => {
+                0u8.hash(hasher);
+                if depth > 0 {
+                    scope.push(id.clone());
+                    body.digest(depth - 1, scope, hasher);
+                    scope.pop();
+                }
+            }
+            Expr::App(m, n) => {
+                1u8.hash(hasher);
+                if depth > 0 {
+                    m.digest(depth - 1, scope, hasher);
+                    n.digest(depth - 1, scope, hasher);
+                }
+            }
+            Expr::Var(id) => match scope.iter().rev().position(|x| x == id) {
+                Some(index) => (2u8, index).hash(hasher),
+                None => (3u8, id).hash(hasher),
+            },
+        }
+    }
+
+    // Alpha-equivalence that, unlike `exact_equivalence`, also accepts open
+    // terms: free variables must match by name.
+    pub fn alpha_eq(&self, other: &Expr) -> bool {
+        self.alpha_eq_with(other, &mut vec![], &mut vec![])
+    }
+
+    fn alpha_eq_with(&self, other: &Expr, ctx1: &mut Vec<Id>, ctx2: &mut Vec<Id>) -> bool {
+        match (self, other) {
+            (Expr::Lam(x, e1), Expr::Lam(y, e2)) => {
+                ctx1.push(x.clone());
+                ctx2.push(y.clone());
+                let eq = e1.alpha_eq_with(e2, ctx1, ctx2);
+                ctx1.pop();
+                ctx2.pop();
+                eq
+            }
+            (Expr::App(m1, n1), Expr::App(m2, n2)) => {
+                m1.alpha_eq_with(m2, ctx1, ctx2) && n1.alpha_eq_with(n2, ctx1, ctx2)
+            }
+            (Expr::Var(x), Expr::Var(y)) => {
+                match (
+                    ctx1.iter().rposition(|v| v == x),
+                    ctx2.iter().rposition(|v| v == y),
+                ) {
+                    (Some(i), Some(j)) => i == j,
+                    (None, None) => x == y,
+                    _ => false,
+                }
+            }
+            _ => false,
+        }
+    }
+
+    // Capture-avoiding `self[_id := e]`. Walks the term with an explicit stack,
+    // so only binders that need renaming cost a call frame.
+    pub fn substitution(&self, _id: &String, e: &Expr) -> Expr {
+        self.substitution_with(_id, e, &mut |id, avoid| freshen(id, avoid))
+    }
+
+    // `fresh` names a capturing binder given the names it must avoid.
+    pub(crate) fn substitution_with(&self, _id: &String, e: &Expr, fresh: &mut Fresh) -> Expr {
+        let fv = e.fv();
+        let mut todo = vec![Build::Visit(self)];
+        let mut done = vec![];
+        while let Some(task) = todo.pop() {
+            match task {
+                Build::Visit(lam @ Expr::Lam(id, _)) if id == _id => done.push(lam.clone()),
+                Build::Visit(Expr::Lam(id, body)) if !fv.contains(id) => {
+                    todo.push(Build::Lam(id.clone()));
+                    todo.push(Build::Visit(body));
+                }
+                Build::Visit(Expr::Lam(id, body)) => {
+                    let mut avoid = body.all_variables();
+                    avoid.extend(e.all_variables());
+                    avoid.push(_id.clone());
+                    let nid = fresh(id, &avoid);
+                    let body = body.substitution_with(id, &Expr::Var(nid.clone()), fresh);
+                    done.push(Expr::Lam(
+                        nid,
+                        Box::new(body.substitution_with(_id, e, fresh)),
+                    ));
+                }
+                Build::Visit(Expr::App(m, n)) => {
+                    todo.push(Build::App);
+                    todo.push(Build::Visit(n));
+                    todo.push(Build::Visit(m));
+                }
+                Build::Visit(Expr::Var(id)) if id == _id => done.push(e.clone()),
+                Build::Visit(var) => done.push(var.clone()),
+                Build::Lam(id) => {
+                    let body = done.pop().unwrap();
+                    done.push(Expr::Lam(id, Box::new(body)));
+                }
+                Build::App => {
+                    let n = done.pop().unwrap();
+                    let m = done.pop().unwrap();
+                    done.push(Expr::App(Box::new(m), Box::new(n)));
+                }
+            }
+        }
+        done.pop().unwrap()
+    }
+
+    // Replaces every `id` of `bindings` at once, so a right-hand side is never
+    // itself substituted into; if a name is bound twice the first binding
+    // wins. Binders that would capture a free variable of a right-hand side
+    // are renamed.
+    pub fn subst_many(&self, bindings: &[(Id, Expr)]) -> Expr {
+        match self {
+            Expr::Var(x) => match bindings.iter().find(|(id, _)| id == x) {
+                Some((_, e)) => e.clone(),
+                None => self.clone(),
+            },
+            Expr::App(m, n) => Expr::App(
+                Box::new(m.subst_many(bindings)),
+                Box::new(n.subst_many(bindings)),
+            ),
+            Expr::Lam(x, body) => {
+                let mut live: Vec<(Id, Expr)> = bindings
+                    .iter()
+                    .filter(|(id, _)| id != x && body.occurs_free(id))
+                    .cloned()
+                    .collect();
+                if live.is_empty() {
+                    return self.clone();
+                }
+                if live.iter().any(|(_, e)| e.occurs_free(x)) {
+                    let mut avoid = body.all_variables();
+                    for (id, e) in &live {
+                        avoid.push(id.clone());
+                        avoid.extend(e.all_variables());
+                    }
+                    let nid = freshen(x, &avoid);
+                    live.push((x.clone(), Expr::Var(nid.clone())));
+                    Expr::Lam(nid, Box::new(body.subst_many(&live)))
+                } else {
+                    Expr::Lam(x.clone(), Box::new(body.subst_many(&live)))
+                }
+            }
+        }
+    }
+
+    // Replaces every free variable `x` by `f(x)` at once, renaming binders
+    // that would capture.
+    pub fn map_free_vars<F: Fn(&Id) -> Expr>(&self, f: F) -> Expr {
+        let mut free = self.fv();
+        free.sort();
+        free.dedup();
+        let bindings: Vec<(Id, Expr)> = free.into_iter().map(|x| (x.clone(), f(&x))).collect();
+        self.subst_many(&bindings)
+    }
+
+    // Renames the free occurrences of `from` to `to`.
+    pub fn rename(&self, from: &str, to: &str) -> Expr {
+        self.substitution(&from.to_string(), &Expr::var(to))
+    }
+
+    // Renames every binder `x` to `f(x)` and its occurrences with it; free
+    // variables stay. A new name that is free in the term or already taken
+    // by an enclosing binder is freshened instead, so nothing is captured:
+    // sending `x` and `y` to `z` makes `λx. λy. x y` into `λz. λz'. z z'`.
+    pub fn map_binders(&self, mut f: impl FnMut(&Id) -> Id) -> Expr {
+        let mut avoid = self.fv();
+        self.map_binders_with(&mut f, &mut vec![], &mut avoid)
+    }
+
+    // `scope` pairs each enclosing binder with its new name, innermost last;
+    // `avoid` holds the free variables and those new names.
+    fn map_binders_with(
+        &self,
+        f: &mut dyn FnMut(&Id) -> Id,
+        scope: &mut Vec<(Id, Id)>,
+        avoid: &mut Vec<Id>,
+    ) -> Expr {
+        match self {
+            Expr::Lam(x, body) => {
+                let wanted = f(x);
+                let new = if avoid.contains(&wanted) {
+                    freshen(&wanted, avoid)
+                } else {
+                    wanted
+                };
+                scope.push((x.clone(), new.clone()));
+                avoid.push(new.clone());
+                let body = body.map_binders_with(f, scope, avoid);
+                avoid.pop();
+                scope.pop();
+                Expr::Lam(new, Box::new(body))
+            }
+            Expr::App(m, n) => {
+                let m = m.map_binders_with(f, scope, avoid);
+                m.apply(&n.map_binders_with(f, scope, avoid))
+            }
+            Expr::Var(x) => match scope.iter().rev().find(|(old, _)| old == x) {
+                Some((_, new)) => Expr::Var(new.clone()),
+                None => self.clone(),
+            },
+        }
+    }
+
+    // Binders of `self` that would capture a free variable of `e` if `id`
+    // were replaced by `e` without renaming, i.e. the cases in which
+    // `substitution` has to pick a fresh name.
+    pub fn would_capture(&self, id: &Id, e: &Expr) -> Vec<Id> {
+        let mut captured = vec![];
+        self.would_capture_with(id, &e.fv(), &mut vec![], &mut captured);
+        captured
+    }
+
+    fn would_capture_with(
+        &self,
+        id: &Id,
+        fv: &Vec<Id>,
+        scope: &mut Vec<Id>,
+        captured: &mut Vec<Id>,
+    ) {
+        match self {
+            Expr::Lam(x, _) if x == id => {}
+            Expr::Lam(x, body) => {
+                scope.push(x.clone());
+                body.would_capture_with(id, fv, scope, captured);
+                scope.pop();
+            }
+            Expr::App(m, n) => {
+                m.would_capture_with(id, fv, scope, captured);
+                n.would_capture_with(id, fv, scope, captured);
+            }
+            Expr::Var(x) if x == id => {
+                for binder in scope.iter() {
+                    if fv.contains(binder) && !captured.contains(binder) {
+                        captured.push(binder.clone());
+                    }
+                }
+            }
+            Expr::Var(_) => {}
+        }
+    }
+
+    // `would_capture`, reporting where the capturing binders are instead of
+    // what they bind: the path of each one, in the order they are reached.
+    pub fn capture_sites(&self, id: &str, e: &Expr) -> Vec<Path> {
+        let mut sites = vec![];
+        self.capture_sites_with(id, &e.fv(), &mut vec![], &mut vec![], &mut sites);
+        sites
+    }
+
+    fn capture_sites_with(
+        &self,
+        id: &str,
+        fv: &Vec<Id>,
+        path: &mut Path,
+        // Enclosing binders with the length of the path to them.
+        scope: &mut Vec<(Id, usize)>,
+        sites: &mut Vec<Path>,
+    ) {
+        match self {
+            Expr::Lam(x, _) if x == id => {}
+            Expr::Lam(x, body) => {
+                scope.push((x.clone(), path.len()));
+                path.push(Dir::LamBody);
+                body.capture_sites_with(id, fv, path, scope, sites);
+                path.pop();
+                scope.pop();
+            }
+            Expr::App(m, n) => {
+                path.push(Dir::AppLeft);
+                m.capture_sites_with(id, fv, path, scope, sites);
+                path.pop();
+                path.push(Dir::AppRight);
+                n.capture_sites_with(id, fv, path, scope, sites);
+                path.pop();
+            }
+            Expr::Var(x) if x == id => {
+                for (binder, depth) in scope.iter() {
+                    let site = &path[..*depth];
+                    if fv.contains(binder) && !sites.iter().any(|s| s == site) {
+                        sites.push(site.to_vec());
+                    }
+                }
+            }
+            Expr::Var(_) => {}
+        }
+    }
+
+    pub fn subterm(&self, path: &[Dir]) -> Option<&Expr> {
+        match (path.split_first(), self) {
+            (None, _) => Some(self),
+            (Some((Dir::LamBody, rest)), Expr::Lam(_, body)) => body.subterm(rest),
+            (Some((Dir::AppLeft, rest)), Expr::App(m, _)) => m.subterm(rest),
+            (Some((Dir::AppRight, rest)), Expr::App(_, n)) => n.subterm(rest),
+            _ => None,
+        }
+    }
+
+    // Calls `visit` on every subterm with its path, outermost first and left
+    // to right, until it returns `true`.
+    pub(crate) fn walk_paths(&self, mut visit: impl FnMut(&Path, &Expr) -> bool) {
+        let mut path = vec![];
+        // `None` steps back out of the child entered last.
+        let mut todo = vec![Some((None, self))];
+        while let Some(item) = todo.pop() {
+            let Some((dir, e)) = item else {
+                path.pop();
+                continue;
+            };
+            if let Some(dir) = dir {
+                path.push(dir);
+                todo.push(None);
+            }
+            if visit(&path, e) {
+                return;
+            }
+            match e {
+                Expr::Lam(_, body) => todo.push(Some((Some(Dir::LamBody), body))),
+                Expr::App(m, n) => {
+                    todo.push(Some((Some(Dir::AppRight), n)));
+                    todo.push(Some((Some(Dir::AppLeft), m)));
+                }
+                Expr::Var(_) => {}
+            }
+        }
+    }
+
+    // Whether `needle` occurs in the term, up to alpha: as a subtree, so
+    // its free variables must be free there too, under the same names.
+    pub fn is_subterm(&self, needle: &Expr) -> bool {
+        let fv = needle.fv();
+        let mut found = false;
+        self.walk_paths(|path, e| {
+            found = e.alpha_eq(needle) && self.free_at(path, &fv);
+            found
+        });
+        found
+    }
+
+    // The paths to every occurrence `is_subterm` looks for, outermost first
+    // and left to right. They never nest: a proper subterm is smaller than
+    // the term, so it cannot be alpha-equivalent to it.
+    pub fn find_subterm(&self, needle: &Expr) -> Vec<Path> {
+        let fv = needle.fv();
+        let mut paths = vec![];
+        self.walk_paths(|path, e| {
+            if e.alpha_eq(needle) && self.free_at(path, &fv) {
+                paths.push(path.clone());
+            }
+            false
+        });
+        paths
+    }
+
+    // Replaces every occurrence `find_subterm` finds of `pattern` by
+    // `replacement`. A purely syntactic rewrite, not a reduction: the
+    // replacement is not searched again, so `λx. x` rewritten to
+    // `(λx. x) (λx. x)` terminates. Binders above an occurrence that would
+    // capture a free variable of `replacement` are renamed, as in
+    // `substitution`.
+    pub fn rewrite(&self, pattern: &Expr, replacement: &Expr) -> Expr {
+        let paths = self.find_subterm(pattern);
+        if paths.is_empty() {
+            return self.clone();
+        }
+        // Every occurrence becomes a variable no binder uses, which
+        // `substitution` then replaces without capture.
+        let mut avoid = self.all_variables();
+        avoid.extend(replacement.all_variables());
+        let hole = freshen("hole", &avoid);
+        let mut holed = self.clone();
+        for path in &paths {
+            holed = holed.replace_at(path, &|_| Some(Expr::var(&hole))).unwrap();
+        }
+        holed.substitution(&hole, replacement)
+    }
+
+    // Whether none of `ids` is bound by a lambda on the way to `path`.
+    fn free_at(&self, path: &[Dir], ids: &[Id]) -> bool {
+        let mut e = self;
+        for dir in path {
+            e = match (dir, e) {
+                (Dir::LamBody, Expr::Lam(id, body)) if !ids.contains(id) => body,
+                (Dir::AppLeft, Expr::App(m, _)) => m,
+                (Dir::AppRight, Expr::App(_, n)) => n,
+                _ => return false,
+            };
+        }
+        true
+    }
+
+    // Rebuilds the term with the node at `path` replaced by `f` applied to it.
+    pub(crate) fn replace_at(
+        &self,
+        path: &[Dir],
+        f: &dyn Fn(&Expr) -> Option<Expr>,
+    ) -> Option<Expr> {
+        match (path.split_first(), self) {
+            (None, _) => f(self),
+            (Some((Dir::LamBody, rest)), Expr::Lam(id, body)) => {
+                Some(Expr::Lam(id.clone(), Box::new(body.replace_at(rest, f)?)))
+            }
+            (Some((Dir::AppLeft, rest)), Expr::App(m, n)) => {
+                Some(Expr::App(Box::new(m.replace_at(rest, f)?), n.clone()))
+            }
+            (Some((Dir::AppRight, rest)), Expr::App(m, n)) => {
+                Some(Expr::App(m.clone(), Box::new(n.replace_at(rest, f)?)))
+            }
+            _ => None,
+        }
+    }
+
+    // Largest structural prefix shared by both terms, with the hole `_` where
+    // they diverge: `λx. f a` and `λx. f b` give `λx. f _`. Binders must have
+    // the same name to be shared.
+    pub fn common_prefix(&self, other: &Expr) -> Expr {
+        match (self, other) {
+            (Expr::Lam(x, e1), Expr::Lam(y, e2)) if x == y => {
+                Expr::Lam(x.clone(), Box::new(e1.common_prefix(e2)))
+            }
+            (Expr::App(m1, n1), Expr::App(m2, n2)) => Expr::App(
+                Box::new(m1.common_prefix(m2)),
+                Box::new(n1.common_prefix(n2)),
+            ),
+            (Expr::Var(x), Expr::Var(y)) if x == y => self.clone(),
+            _ => Expr::var("_"),
+        }
+    }
+
+    // Renames binders to `x0`, `x1`, ... in the order they appear, skipping
+    // names that occur free, so alpha-equivalent terms print identically.
+    // Free variables keep their names.
+    pub fn to_canonical(&self) -> Expr {
+        let fv = self.fv();
+        let mut counter = 0;
+        self.to_canonical_with(&fv, &mut counter, &mut vec![])
+    }
+
+    fn to_canonical_with(
+        &self,
+        fv: &Vec<Id>,
+        counter: &mut usize,
+        scope: &mut Vec<(Id, Id)>,
+    ) -> Expr {
+        match self {
+            Expr::Lam(id, body) => {
+                let mut name = format!("x{counter}");
+                *counter += 1;
+                while fv.contains(&name) {
+                    name = format!("x{counter}");
+                    *counter += 1;
+                }
+                scope.push((id.clone(), name.clone()));
+                let body = body.to_canonical_with(fv, counter, scope);
+                scope.pop();
+                Expr::Lam(name, Box::new(body))
+            }
+            Expr::App(m, n) => Expr::App(
+                Box::new(m.to_canonical_with(fv, counter, scope)),
+                Box::new(n.to_canonical_with(fv, counter, scope)),
+            ),
+            Expr::Var(id) => match scope.iter().rev().find(|(x, _)| x == id) {
+                Some((_, name)) => Expr::Var(name.clone()),
+                None => self.clone(),
+            },
+        }
+    }
+
+    pub fn apply(&self, other: &Expr) -> Expr {
+        Expr::App(Box::new(self.clone()), Box::new(other.clone()))
+    }
+
+    // `self a1 a2 ... an`; `self` itself when `args` is empty.
+    pub fn apply_all(&self, args: &[Expr]) -> Expr {
+        args.iter().fold(self.clone(), |acc, arg| acc.apply(arg))
+    }
+
+    // `apply_all` that moves the head and the arguments in place of cloning.
+    pub fn apply_all_owned(self, args: Vec<Expr>) -> Expr {
+        Expr::app_spine(self, args)
+    }
+
+    // `λx. self (g x)`, with `x` primed until it captures nothing.
+    pub fn compose(&self, g: &Expr) -> Expr {
+        let mut free = self.fv();
+        free.extend(g.fv());
+        let x = if free.iter().any(|id| id == "x") {
+            freshen("x", &free)
+        } else {
+            "x".to_string()
+        };
+        Expr::lambda(&x, self.apply(&g.apply(&Expr::var(&x))))
+    }
+
+    // `λx. self (self x)`.
+    pub fn twice(&self) -> Expr {
+        self.compose(self)
+    }
+
+    pub fn call<T: terms::ToLambda>(&self, args: &[T]) -> Expr {
+        terms::applied(self.clone(), args)
+    }
+
+    // Applies `args` in order, contracting only the head redexes that consume
+    // them. The bodies are left as they are, so this is much cheaper than
+    // normalizing the applied term.
+    pub fn partial_apply(&self, args: &[Expr]) -> Expr {
+        args.iter().fold(self.clone(), |acc, arg| match acc {
+            Expr::Lam(id, body) => body.substitution(&id, arg),
+            _ => acc.apply(arg),
+        })
+    }
+
+    pub fn lambda(id: &str, expr: Expr) -> Expr {
+        Expr::Lam(id.to_string(), Box::new(expr))
+    }
+
+    pub fn var(id: &str) -> Expr {
+        Expr::Var(id.to_string())
+    }
+
+    // `head a1 ... an` from the head and its arguments, the inverse of
+    // `to_app_vec`.
+    pub fn app_spine(head: Expr, args: impl IntoIterator<Item = Expr>) -> Expr {
+        args.into_iter()
+            .fold(head, |acc, arg| Expr::App(Box::new(acc), Box::new(arg)))
+    }
+
+    // Splits `head a1 ... an` into its head and arguments, which need not
+    // be variables. Only the spine is taken apart: an argument that is itself
+    // an application stays whole, so `f (g x)` gives `f` and `[g x]`.
+    pub fn to_app_vec(&self) -> (&Expr, Vec<&Expr>) {
+        let mut args = vec![];
+        let mut head = self;
+        while let Expr::App(m, n) = head {
+            args.push(n.as_ref());
+            head = m;
+        }
+        args.reverse();
+        (head, args)
+    }
+}
@@ -0,0 +1,65 @@
+// Integers as differences of naturals: the pair `(a, b)` stands for `a - b`.
+// Nothing keeps pairs canonical, so `3 - 5` comes out as `(3, 5)` rather than
+// `(0, 2)`; `to_i32` normalizes both components and subtracts, which makes
+// every representation of the same integer decode alike.
+
+use super::terms::{self, ChurchNumeral};
+use super::*;
+
+pub fn of_int(i: i32) -> Expr {
+    terms::tuple(
+        &i.max(0).unsigned_abs().to_church(),
+        &i.min(0).unsigned_abs().to_church(),
+    )
+}
+
+pub fn neg() -> Expr {
+    // λp. (second p, first p)
+    Expr::lambda(
+        "p",
+        terms::tuple(
+            &terms::second().apply(&Expr::var("p")),
+            &terms::first().apply(&Expr::var("p")),
+        ),
+    )
+}
+
+pub fn add_int() -> Expr {
+    // λp q. (first p + first q, second p + second q)
+    let component = |proj: Expr| {
+        terms::add()
+            .apply(&proj.apply(&Expr::var("p")))
+            .apply(&proj.apply(&Expr::var("q")))
+    };
+    Expr::lambda(
+        "p",
+        Expr::lambda(
+            "q",
+            terms::tuple(&component(terms::first()), &component(terms::second())),
+        ),
+    )
+}
+
+pub fn sub_int() -> Expr {
+    // λp q. add_int p (neg q)
+    Expr::lambda(
+        "p",
+        Expr::lambda(
+            "q",
+            add_int()
+                .apply(&Expr::var("p"))
+                .apply(&neg().apply(&Expr::var("q"))),
+        ),
+    )
+}
+
+impl Expr {
+    // Decodes an `integers` pair by normalizing both components, each within
+    // the default budget that `to_numeral` uses.
+    pub fn to_i32(&self) -> Result<i32, NotANumeral> {
+        let component = |proj: Expr| proj.apply(self).to_numeral();
+        let positive = component(terms::first())?;
+        let negative = component(terms::second())?;
+        i32::try_from(i64::from(positive) - i64::from(negative)).map_err(|_| NotANumeral)
+    }
+}
@@ -0,0 +1,48 @@
+use super::*;
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ExprId(usize);
+
+// Stores each term once per alpha-equivalence class. Terms are keyed by
+// their canonical form (binders renamed `x0`, `x1`, ...), which, like the
+// de Bruijn form, alpha-equivalent terms share, but which also allows free
+// variables.
+#[derive(Debug, Default)]
+pub struct Interner {
+    terms: Vec<Expr>,
+    ids: HashMap<Expr, ExprId>,
+}
+
+impl Interner {
+    pub fn new() -> Interner {
+        Interner::default()
+    }
+
+    // The id of `e`'s class, adding `e` as its representative if the class
+    // is new.
+    pub fn intern(&mut self, e: &Expr) -> ExprId {
+        let key = e.to_canonical();
+        if let Some(&id) = self.ids.get(&key) {
+            return id;
+        }
+        let id = ExprId(self.terms.len());
+        self.terms.push(e.clone());
+        self.ids.insert(key, id);
+        id
+    }
+
+    // The first term interned under `id`, with its original names.
+    pub fn resolve(&self, id: ExprId) -> &Expr {
+        &self.terms[id.0]
+    }
+
+    // Number of distinct classes interned so far.
+    pub fn len(&self) -> usize {
+        self.terms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+}
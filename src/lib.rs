@@ -0,0 +1,2269 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+// Without `std` the crate still builds, prints and converts terms; the parts
+// that need hash maps or a clock are left out.
+#[cfg(not(feature = "alloc"))]
+compile_error!("lcrs needs the `alloc` feature");
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+pub mod builder;
+pub mod combinatory;
+pub mod debruijn;
+mod diff;
+pub mod expr;
+pub mod integers;
+#[cfg(feature = "std")]
+mod intern;
+mod parser;
+mod pattern;
+mod print;
+mod recognize;
+pub mod reduce;
+pub mod scott;
+pub mod stlc;
+pub mod terms;
+mod visit;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use debruijn::{DeBrujin, DeBrujinLevel};
+pub use diff::{DiffNode, Mismatch};
+#[cfg(feature = "std")]
+pub use expr::Env;
+pub use expr::{AlphaEq, Dir, Expr, FreshSupply, Id, Path};
+#[cfg(feature = "std")]
+pub use intern::{ExprId, Interner};
+pub use parser::{ParseError, Program};
+pub use pattern::{Bindings, Pattern};
+pub use print::{Nameable, Node, Notation, PrintStyle, Span, TokenKind};
+pub use reduce::{
+    strategies_agree_on, ByName, ByValue, Divergence, EvalConfig, NotARedex, RedexSelector,
+    ReductionSteps, Strategy, TraceEntry,
+};
+pub use terms::{EvalError, NotANumeral, NotANumeralList, NotAPair, NotAString};
+pub use visit::Visitor;
+
+use expr::{freshen, Build, Fresh};
+
+// `assert_reduces_to!(term, expected)` normalizes both sides in normal order
+// and panics, printing the two normal forms and where they first differ,
+// unless they are alpha-equivalent. `expected` is anything `ToLambda`: a
+// numeral, a boolean or another term. A side without a normal form within
+// the default step budget fails the assertion instead of hanging.
+#[macro_export]
+macro_rules! assert_reduces_to {
+    ($term:expr, $expected:expr $(,)?) => {{
+        let cfg = $crate::EvalConfig::default();
+        let normalize = |source: &str, e: &$crate::Expr| match e.normalize_with(&cfg) {
+            Ok(nf) => nf,
+            Err(err) => panic!(
+                "assertion failed: `{}` has no normal form within {} steps: {:?}",
+                source, cfg.max_steps, err,
+            ),
+        };
+        let actual = normalize(stringify!($term), &$term);
+        let expected = normalize(
+            stringify!($expected),
+            &$crate::terms::ToLambda::to_lambda(&$expected),
+        );
+        if let Err(mismatch) = actual.alpha_eq_explain(&expected) {
+            panic!(
+                "assertion failed: `{}` reduces to `{}`\n  actual normal form: {}\nexpected normal form: {}\n    first difference {}",
+                stringify!($term),
+                stringify!($expected),
+                actual,
+                expected,
+                mismatch,
+            );
+        }
+    }};
+}
+
+#[cfg(all(test, feature = "std"))]
+mod properties {
+    use super::combinatory::{to_ski, CL};
+    use super::terms::{self, Church, ChurchExpr, ChurchNumeral, ToLambda};
+    use super::{
+        scott, AlphaEq, ByName, ByValue, DeBrujin, DiffNode, Dir, EvalConfig, EvalError, Expr,
+        FreshSupply, Id, Interner, NotANumeral, NotANumeralList, NotAPair, NotARedex, NotAString,
+        Notation, Pattern, PrintStyle, TokenKind,
+    };
+    use proptest::prelude::*;
+
+    const NAMES: [&str; 3] = ["x", "y", "z"];
+
+    fn arb_open() -> impl Strategy<Value = Expr> {
+        let leaf = prop::sample::select(&NAMES[..]).prop_map(Expr::var);
+        leaf.prop_recursive(5, 24, 2, |inner| {
+            prop_oneof![
+                (prop::sample::select(&NAMES[..]), inner.clone())
+                    .prop_map(|(id, body)| Expr::lambda(id, body)),
+                (inner.clone(), inner).prop_map(|(m, n)| m.apply(&n)),
+            ]
+        })
+    }
+
+    // Closes over every name that might occur, so the result has no free
+    // variables. Shrinking goes through the open term.
+    fn arb_closed() -> impl Strategy<Value = Expr> {
+        arb_open().prop_map(|e| NAMES.iter().rev().fold(e, |acc, id| Expr::lambda(id, acc)))
+    }
+
+    fn sorted_dedup(mut ids: Vec<Id>) -> Vec<Id> {
+        ids.sort();
+        ids.dedup();
+        ids
+    }
+
+    proptest! {
+        #[test]
+        fn alpha_eq_is_reflexive(e in arb_open()) {
+            prop_assert!(e.alpha_eq(&e));
+        }
+
+        #[test]
+        fn alpha_eq_is_symmetric(a in arb_open(), b in arb_open()) {
+            prop_assert_eq!(a.alpha_eq(&b), b.alpha_eq(&a));
+        }
+
+        #[test]
+        fn debrujin_round_trip_is_stable(e in arb_closed()) {
+            let named = e.debrujin().to_named();
+            prop_assert!(named.debrujin() == e.debrujin());
+            prop_assert!(named.alpha_eq(&e));
+        }
+
+        #[test]
+        fn printing_round_trips(e in arb_open()) {
+            for style in [
+                PrintStyle::UNICODE,
+                PrintStyle::ASCII,
+                PrintStyle::UNICODE | PrintStyle::COLLAPSE,
+                PrintStyle::ASCII | PrintStyle::COLLAPSE,
+            ] {
+                let printed = e.to_string_styled(style);
+                prop_assert_eq!(printed.parse::<Expr>(), Ok(e.clone()), "{}", printed);
+            }
+        }
+
+        #[test]
+        fn subst_many_of_one_binding_is_substitution(e in arb_open(), s in arb_open(), id in prop::sample::select(&NAMES[..])) {
+            let id = id.to_string();
+            let many = e.subst_many(&[(id.clone(), s.clone())]);
+            prop_assert!(many.alpha_eq(&e.substitution(&id, &s)));
+        }
+
+        #[test]
+        fn substitution_free_variables(e in arb_open(), s in arb_open(), id in prop::sample::select(&NAMES[..])) {
+            let id = id.to_string();
+            let mut expected = e.fv();
+            if expected.contains(&id) {
+                expected.retain(|x| x != &id);
+                expected.append(&mut s.fv());
+            }
+            prop_assert_eq!(sorted_dedup(e.substitution(&id, &s).fv()), sorted_dedup(expected));
+        }
+
+        #[test]
+        fn full_reduction_bounded_is_idempotent(e in arb_closed()) {
+            if let Some(nf) = e.full_reduction_bounded(8) {
+                let again = nf.full_reduction_bounded(8);
+                prop_assert!(again.is_some_and(|again| again.alpha_eq(&nf)));
+            }
+        }
+
+        #[test]
+        fn cata_size_matches_size(e in arb_open()) {
+            prop_assert_eq!(e.cata(|_, b| 1 + b, |m, n| 1 + m + n, |_| 1), e.size());
+        }
+
+        #[test]
+        fn named_and_de_bruijn_print_the_same_structure(e in arb_closed()) {
+            let shape = |s: String| s.chars().filter(|c| "λ.() ".contains(*c)).collect::<String>();
+            let named = e.to_string_styled(PrintStyle::UNICODE);
+            prop_assert_eq!(shape(named), shape(e.debrujin().to_string()));
+        }
+
+        #[test]
+        fn levels_agree_with_indices(a in arb_closed(), b in arb_closed()) {
+            let levels = a.debruijn_levels();
+            prop_assert_eq!(levels.to_indices(), a.debrujin());
+            prop_assert_eq!(a.debrujin().to_levels(), levels.clone());
+            prop_assert_eq!(levels == b.debruijn_levels(), a.alpha_eq(&b));
+        }
+
+        #[test]
+        fn normalize_lazy_agrees_with_full_reduction(e in arb_closed()) {
+            if let Some(nf) = e.full_reduction_bounded(50) {
+                prop_assert!(e.normalize_lazy().alpha_eq(&nf), "{}", e);
+            }
+        }
+
+        #[test]
+        fn alpha_eq_newtype_ignores_binder_names(e in arb_open()) {
+            use std::hash::{BuildHasher, RandomState};
+            let hasher = RandomState::new();
+            let canonical = AlphaEq(e.to_canonical());
+            prop_assert_eq!(hasher.hash_one(&canonical), hasher.hash_one(AlphaEq(e.clone())));
+            prop_assert_eq!(canonical, AlphaEq(e));
+        }
+
+        #[test]
+        fn free_var_paths_count_free_occurrences(e in arb_open()) {
+            for id in NAMES {
+                prop_assert_eq!(e.free_var_paths(id).len(), e.free_occurrence_count(id));
+            }
+        }
+
+        #[test]
+        fn map_binders_preserves_alpha_class(e in arb_open()) {
+            let suffixed = e.map_binders(|id| id.clone() + "1");
+            prop_assert!(suffixed.alpha_eq(&e));
+            prop_assert!(e.map_binders(|_| "x".to_string()).alpha_eq(&e));
+        }
+
+        #[test]
+        fn beta_eta_normalize_is_idempotent(e in arb_open()) {
+            if let Ok(normal) = e.beta_eta_normalize(200) {
+                prop_assert!(normal.is_normal() && normal.is_eta_normal());
+                prop_assert_eq!(normal.beta_eta_normalize(200), Ok(normal.clone()));
+            }
+        }
+
+        #[test]
+        fn is_form_of_agrees_with_debrujin(a in arb_closed(), b in arb_closed()) {
+            prop_assert_eq!(a.debrujin().is_form_of(&b), a.debrujin() == b.debrujin());
+            prop_assert!(a.debrujin().is_form_of(&a));
+        }
+
+        #[test]
+        fn eta_long_is_eta_equal_to_the_normal_form(e in arb_open(), arity in 0..3_usize) {
+            if let Some(nf) = e.reduce_with(super::Strategy::Normal, 200) {
+                let long = nf.eta_long(arity);
+                prop_assert!(long.is_normal());
+                prop_assert!(long.eta_reduce().alpha_eq(&nf.eta_reduce()));
+            }
+        }
+
+        #[test]
+        fn normalize_shared_agrees_with_full_reduction(e in arb_closed()) {
+            if let Some(normal) = e.full_reduction_bounded(50) {
+                prop_assert!(e.normalize_shared().alpha_eq(&normal));
+            }
+        }
+
+        #[test]
+        fn fingerprint_is_alpha_invariant(e in arb_open()) {
+            let renamed = e.map_binders(|id| id.clone() + "'");
+            prop_assert_eq!(e.fingerprint(), renamed.fingerprint());
+        }
+
+        #[test]
+        fn numeral_aware_agrees_with_full_reduction(
+            op in prop::sample::select(&["succ", "add", "mul", "exp"][..]),
+            a in 0_u32..5,
+            b in 0_u32..5,
+        ) {
+            let term = match op {
+                "succ" => terms::succ().call(&[a]),
+                "add" => terms::add().call(&[a, b]),
+                "mul" => terms::mul().call(&[a, b]),
+                _ => terms::exp().call(&[a, b]),
+            };
+            let fast = term.reduce_with(super::Strategy::NumeralAware, 10_000).unwrap();
+            prop_assert!(fast.alpha_eq(&term.full_reduction()), "{}", term);
+        }
+    }
+
+    #[test]
+    fn numeral_aware_computes_exp_2_15_in_one_step() {
+        // Dropping the 32768-deep result recurses once per node, more than
+        // the default test thread stack allows.
+        let run = || {
+            let term = terms::exp().call(&[2_u32, 15]);
+            assert!(term.reduce_with(super::Strategy::Normal, 1_000).is_none());
+            let nf = term.reduce_with(super::Strategy::NumeralAware, 2).unwrap();
+            assert_eq!(nf.to_numeral(), Ok(32768));
+        };
+        std::thread::Builder::new()
+            .stack_size(64 << 20)
+            .spawn(run)
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+
+    #[test]
+    fn recognizers_tell_encodings_apart() {
+        let list = terms::cons().call(&[1_u32]).apply(&terms::nil());
+        let omega = "(λx. x x) (λx. x x)".parse::<Expr>().unwrap();
+        let cases = [
+            (terms::add().call(&[2_u32, 1]), [true, false, false]),
+            (terms::not().call(&[false]), [false, true, false]),
+            (list, [false, false, true]),
+            ("λx. x".parse().unwrap(), [false, false, false]),
+            ("f x".parse().unwrap(), [false, false, false]),
+            (omega, [false, false, false]),
+        ];
+        for (e, expected) in cases {
+            let found = [
+                e.is_church_numeral(),
+                e.is_church_bool(),
+                e.is_church_list(),
+            ];
+            assert_eq!(found, expected, "{e}");
+        }
+    }
+
+    #[test]
+    fn apply_all_applies_left_to_right() {
+        let (five, seven) = (5_u32.to_church(), 7_u32.to_church());
+        let sum = terms::add().apply_all(&[five.clone(), seven.clone()]);
+        assert_eq!(sum.full_reduction().to_numeral(), Ok(12));
+        assert_eq!(terms::add().apply_all_owned(vec![five, seven]), sum);
+        assert_eq!(terms::add().apply_all(&[]), terms::add());
+    }
+
+    #[test]
+    fn diff_reports_the_extra_application() {
+        let left = "λx. x x".parse::<Expr>().unwrap();
+        let right = "λx. x".parse::<Expr>().unwrap();
+        assert_eq!(
+            left.diff(&right),
+            vec![DiffNode::LeftOnly {
+                path: vec![Dir::LamBody],
+                subtree: "x x".parse().unwrap(),
+            }]
+        );
+    }
+
+    #[test]
+    fn to_pair_agrees_with_first_and_second() {
+        let a = terms::add().call(&[1_u32, 2]);
+        let b = "(λx. x) y".parse::<Expr>().unwrap();
+        let pair = terms::tuple(&a, &b);
+        let (first, second) = pair.to_pair().unwrap();
+        assert!(first.alpha_eq(&3_u32.to_church()));
+        assert_eq!(second, Expr::var("y"));
+        assert!(first.alpha_eq(&terms::first().apply(&pair).normalize_nbe()));
+        assert!(second.alpha_eq(&terms::second().apply(&pair).normalize_nbe()));
+        assert_eq!(terms::t().to_pair(), Err(NotAPair));
+        let omega = "(λx. x x) (λx. x x)".parse::<Expr>().unwrap();
+        assert_eq!(omega.to_pair(), Err(NotAPair));
+    }
+
+    #[test]
+    fn debrujin_handles_a_thousand_binders() {
+        let deep = (0..1000).rev().fold(Expr::var("x0"), |acc, i| {
+            Expr::lambda(&format!("x{i}"), acc)
+        });
+        let mut term = &deep.debrujin();
+        for _ in 0..1000 {
+            let DeBrujin::Lam(body) = term else {
+                panic!("expected a lambda");
+            };
+            term = body;
+        }
+        assert_eq!(term, &DeBrujin::Var(999));
+    }
+
+    #[test]
+    fn app_spine_inverts_to_app_vec() {
+        for src in ["f a b c", "f (g x) (λy. y y)", "x"] {
+            let e = src.parse::<Expr>().unwrap();
+            let (head, args) = e.to_app_vec();
+            assert_eq!(Expr::app_spine(head.clone(), args.into_iter().cloned()), e);
+        }
+        let args = ["a", "b", "c"].map(Expr::var);
+        let e = Expr::app_spine(Expr::var("f"), args.clone());
+        assert_eq!(e, "((f a) b) c".parse().unwrap());
+        assert_eq!(e.to_app_vec(), (&Expr::var("f"), args.iter().collect()));
+    }
+
+    #[test]
+    fn to_numeral_keeps_arguments_whole() {
+        let one = terms::succ().apply(&Expr::church_zero()).full_reduction();
+        assert_eq!(one.to_numeral(), Ok(1));
+        let three = "λg y. g (g (g y))".parse::<Expr>().unwrap();
+        assert_eq!(three.to_numeral(), Ok(3));
+        let spliced = "λf x. f f x".parse::<Expr>().unwrap();
+        assert_eq!(spliced.to_numeral(), Err(super::NotANumeral));
+    }
+
+    #[test]
+    fn reduce_to_numeral_and_bool_decode_within_budget() {
+        let sum = terms::add().call(&[5_u32, 7]);
+        assert_eq!(sum.reduce_to_numeral(1_000), Ok(12));
+        assert_eq!(sum.reduce_to_bool(1_000), Err(EvalError::NotABool));
+        assert_eq!(terms::not().call(&[true]).reduce_to_bool(1_000), Ok(false));
+        let omega = "(λx. x x) (λx. x x)".parse::<Expr>().unwrap();
+        assert_eq!(omega.reduce_to_numeral(1_000), Err(EvalError::Diverged));
+        assert_eq!(omega.reduce_to_bool(1_000), Err(EvalError::Diverged));
+    }
+
+    #[test]
+    fn interner_shares_ids_up_to_alpha() {
+        let mut interner = Interner::new();
+        let x = "λx. x".parse::<Expr>().unwrap();
+        let id = interner.intern(&x);
+        assert_eq!(interner.intern(&"λy. y".parse().unwrap()), id);
+        assert_ne!(interner.intern(&"λx. y".parse().unwrap()), id);
+        assert_eq!(interner.resolve(id), &x);
+        assert_eq!(interner.len(), 2);
+    }
+
+    #[test]
+    fn eta_eq_identifies_eta_variants() {
+        let parse = |src: &str| src.parse::<Expr>().unwrap();
+        assert!(parse("λx. f x").eta_eq(&Expr::var("f")));
+        assert!(!parse("λx. x x").eta_eq(&parse("λx. x")));
+        let and = parse("λp q. p (λz. q z) (λx y. y)");
+        assert!(!and.normalize_nbe().alpha_eq(&terms::and()));
+        assert!(and.eta_eq(&terms::and()));
+        assert!(parse("λp. and p")
+            .substitution(&"and".to_string(), &terms::and())
+            .eta_eq(&terms::and()));
+        assert!(parse("λn. succ n")
+            .substitution(&"succ".to_string(), &terms::succ())
+            .eta_eq(&terms::succ()));
+        let omega = parse("(λx. x x) (λx. x x)");
+        assert!(!omega.eta_eq(&omega));
+    }
+
+    #[test]
+    fn de_bruijn_prints_with_minimal_parentheses() {
+        let cases = [
+            ("λx. x", "λ. 0"),
+            ("λx y. x", "λ. λ. 1"),
+            ("(λx. x) λx. x", "(λ. 0) λ. 0"),
+            ("λf x. f (f x)", "λ. λ. 1 (1 0)"),
+            ("λf x. f x x", "λ. λ. 1 0 0"),
+            ("λx. x (λy. y) x", "λ. 0 (λ. 0) 0"),
+        ];
+        for (src, expected) in cases {
+            let e = src.parse::<Expr>().unwrap();
+            assert_eq!(e.to_string(), src);
+            assert_eq!(e.debrujin().to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn skk_behaves_as_i() {
+        let skk = CL::S.app(CL::K).app(CL::K);
+        assert_eq!(skk.normalize(), skk);
+        let a = CL::Var("a".to_string());
+        assert_eq!(skk.app(a.clone()).normalize(), a);
+        assert_eq!(CL::I.app(a.clone()).normalize(), a);
+    }
+
+    #[test]
+    fn to_ski_keeps_the_normal_form() {
+        let samples = [
+            terms::and(),
+            terms::not().apply(&terms::t()),
+            terms::succ().call(&[2_u32]),
+            terms::add().call(&[1_u32, 2]),
+            terms::combinators::b(),
+            "λg f x. f (g x x)".parse::<Expr>().unwrap(),
+        ];
+        for e in samples {
+            let nf = e.full_reduction();
+            let ski = to_ski(&e);
+            assert!(ski.to_lambda().full_reduction().alpha_eq(&nf), "{}", e);
+            assert!(
+                ski.normalize().to_lambda().full_reduction().alpha_eq(&nf),
+                "{}",
+                e
+            );
+        }
+    }
+
+    #[test]
+    fn div_and_mod_decode() {
+        assert_eq!(
+            terms::div().call(&[17_u32, 5]).reduce_to_numeral(10_000),
+            Ok(3)
+        );
+        assert_eq!(
+            terms::mod_().call(&[17_u32, 5]).reduce_to_numeral(10_000),
+            Ok(2)
+        );
+        assert_eq!(
+            terms::div().call(&[3_u32, 5]).reduce_to_numeral(10_000),
+            Ok(0)
+        );
+        assert_eq!(
+            terms::mod_().call(&[3_u32, 5]).reduce_to_numeral(10_000),
+            Ok(3)
+        );
+    }
+
+    #[test]
+    fn capture_sites_finds_the_capturing_binder() {
+        let parse = |src: &str| src.parse::<Expr>().unwrap();
+        let y = Expr::var("y");
+        assert_eq!(parse("λy. x").capture_sites("x", &y), vec![vec![]]);
+        let e = parse("z (λy. λw. x) (λy. y)");
+        assert_eq!(
+            e.capture_sites("x", &y),
+            vec![vec![Dir::AppLeft, Dir::AppRight]]
+        );
+        assert_eq!(
+            e.capture_sites("x", &y).len(),
+            e.would_capture(&"x".to_string(), &y).len()
+        );
+        assert!(parse("λx. λy. x").capture_sites("x", &y).is_empty());
+    }
+
+    #[test]
+    fn truth_table_of_and_counts_up_from_false() {
+        let table = terms::boolean_truth_table(&terms::and(), 2, 100);
+        assert_eq!(
+            table,
+            [
+                (vec![false, false], Some(false)),
+                (vec![false, true], Some(false)),
+                (vec![true, false], Some(false)),
+                (vec![true, true], Some(true)),
+            ]
+        );
+        // `λx y. y` applied once is not a boolean, so that row is `None`.
+        let second = terms::boolean_truth_table(&terms::f(), 1, 100);
+        assert_eq!(second, [(vec![false], None), (vec![true], None)]);
+        // With no inputs the table has one row, `op` itself.
+        assert_eq!(
+            terms::boolean_truth_table(&terms::t(), 0, 100),
+            [(vec![], Some(true))]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "too many rows")]
+    fn truth_table_rejects_arities_past_usize() {
+        terms::boolean_truth_table(&terms::t(), usize::BITS as usize, 100);
+    }
+
+    #[test]
+    fn decoders_give_up_without_a_normal_form() {
+        let omega = "(λx. x x) (λx. x x)".parse::<Expr>().unwrap();
+        assert_eq!(omega.to_numeral_vec(), Err(NotANumeralList::NotAList));
+        assert_eq!(omega.to_church_string(), Err(NotAString::NotAList));
+        assert_eq!(omega.to_bool_vec(), None);
+        assert_eq!(omega.cmp_numeral(&Expr::church_one()), Err(NotANumeral));
+        assert_eq!(omega.to_i32(), Err(NotANumeral));
+    }
+
+    #[test]
+    fn church_strings_round_trip() {
+        let hi = Expr::church_of_str("hi").unwrap();
+        assert_eq!(hi.to_numeral_vec(), Ok(vec![104, 105]));
+        assert_eq!(hi.to_church_string(), Ok("hi".to_string()));
+        let built = terms::cons().apply(&104_u32.to_church()).apply(
+            &terms::cons()
+                .apply(&105_u32.to_church())
+                .apply(&terms::nil()),
+        );
+        assert_eq!(built.to_church_string(), Ok("hi".to_string()));
+        assert_eq!(
+            Expr::church_of_str("").unwrap().to_church_string(),
+            Ok(String::new())
+        );
+        assert_eq!(Expr::church_of_str("héllo"), Err(NotAString::NotAscii(1)));
+
+        let list = |elems: &[Expr]| {
+            elems
+                .iter()
+                .rev()
+                .fold(terms::nil(), |t, h| terms::cons().apply(h).apply(&t))
+        };
+        assert_eq!(terms::t().to_church_string(), Err(NotAString::NotAList));
+        assert_eq!(
+            list(&[104_u32.to_church(), terms::t()]).to_church_string(),
+            Err(NotAString::NotANumeral(1))
+        );
+        assert_eq!(
+            list(&[200_u32.to_church()]).to_church_string(),
+            Err(NotAString::NotAscii(0))
+        );
+    }
+
+    #[test]
+    fn equivalence_decides_the_boolean_algebra() {
+        let (t, f) = (terms::t(), terms::f());
+        for (p, q) in [(true, true), (true, false), (false, true), (false, false)] {
+            let and = terms::and().call(&[p, q]).reduction();
+            let or = terms::or().call(&[p, q]).reduction();
+            assert_eq!(and.equivalence(&(p && q).to_lambda()), Some(true));
+            assert_eq!(and.equivalence(&(!(p && q)).to_lambda()), Some(false));
+            assert_eq!(or.equivalence(&(p || q).to_lambda()), Some(true));
+            assert_eq!(or.equivalence(&(!(p || q)).to_lambda()), Some(false));
+        }
+        assert_eq!(
+            terms::not().apply(&t).reduction().equivalence(&f),
+            Some(true)
+        );
+        assert_eq!(
+            terms::not().apply(&f).reduction().equivalence(&f),
+            Some(false)
+        );
+        let omega = "(λx. x x) (λx. x x)".parse::<Expr>().unwrap();
+        assert_eq!(omega.equivalence(&t), None);
+    }
+
+    #[test]
+    fn literals_desugar_to_church_encodings() {
+        let parse = |src: &str| src.parse::<Expr>().unwrap();
+        assert_eq!(parse("5"), 5_u32.to_church());
+        assert_eq!(parse("(true) false"), terms::t().apply(&terms::f()));
+        let sum = parse("add 5 7").substitution(&"add".to_string(), &terms::add());
+        assert_eq!(sum.reduce_to_numeral(10_000), Ok(12));
+        assert!("λtrue. true".parse::<Expr>().is_err());
+        assert_eq!("99999999999".parse::<Expr>().unwrap_err().offset, 0);
+    }
+
+    #[test]
+    fn shift_and_subst_match_tapl() {
+        use DeBrujin::{App, Lam, Var};
+        let lam = |b| Lam(Box::new(b));
+        let app = |m, n| App(Box::new(m), Box::new(n));
+        // ↑² (λ. λ. 1 (0 2)) = λ. λ. 1 (0 4)
+        let e = lam(lam(app(Var(1), app(Var(0), Var(2)))));
+        assert_eq!(e.shift(2, 0), lam(lam(app(Var(1), app(Var(0), Var(4))))));
+        // ↑² (λ. 0 1 (λ. 0 1 2)) = λ. 0 3 (λ. 0 1 4)
+        let e = lam(app(
+            app(Var(0), Var(1)),
+            lam(app(app(Var(0), Var(1)), Var(2))),
+        ));
+        let shifted = lam(app(
+            app(Var(0), Var(3)),
+            lam(app(app(Var(0), Var(1)), Var(4))),
+        ));
+        assert_eq!(e.shift(2, 0), shifted);
+        assert_eq!(shifted.shift(-2, 0), e);
+        // [0 ↦ 1] (0 (λ. λ. 2)) = 1 (λ. λ. 3)
+        let e = app(Var(0), lam(lam(Var(2))));
+        assert_eq!(e.subst(0, &Var(1)), app(Var(1), lam(lam(Var(3)))));
+    }
+
+    #[test]
+    #[should_panic(expected = "falls below cutoff")]
+    fn shift_rejects_underflow() {
+        DeBrujin::Lam(Box::new(DeBrujin::Var(1))).shift(-2, 0);
+    }
+
+    #[test]
+    fn numeral_shorthands_match_to_church() {
+        assert!(Expr::church_zero().alpha_eq(&0_u32.to_church()));
+        assert!(Expr::church_one().alpha_eq(&1_u32.to_church()));
+        assert_eq!(terms::nat(7), 7_u32.to_church());
+        let id: Expr = Default::default();
+        assert!(id.alpha_eq(&terms::combinators::i()));
+        let applied = id.apply(&Expr::var("a"));
+        assert_eq!(
+            applied.reduce_with(super::Strategy::Normal, 1),
+            Some(Expr::var("a"))
+        );
+    }
+
+    #[test]
+    fn depth_limit_stops_runaway_terms() {
+        // Each normal-order step wraps one more `f` around the redex.
+        let runaway = "(λx. x x) (λy. f (y y))".parse::<Expr>().unwrap();
+        assert_eq!(
+            runaway.reduce_with_depth(super::Strategy::Normal, 1_000_000, 50),
+            Err(super::Divergence::TooDeep { step: 47 })
+        );
+        assert_eq!(
+            runaway.reduce_with_depth(super::Strategy::Normal, 10, usize::MAX),
+            Err(super::Divergence::OutOfSteps)
+        );
+        let sum = terms::add().call(&[2_u32, 3]);
+        assert_eq!(
+            sum.reduce_with_depth(super::Strategy::Normal, 1_000, 20),
+            Ok(5_u32.to_church())
+        );
+        assert_eq!(
+            sum.reduce_with_depth(super::Strategy::Normal, 1_000, 2),
+            Err(super::Divergence::TooDeep { step: 0 })
+        );
+    }
+
+    #[test]
+    fn list_functions_decode() {
+        let nats = |ns: &[u32]| {
+            ns.iter().rev().fold(terms::nil(), |t, &h| {
+                terms::cons().call(&[h.to_church(), t])
+            })
+        };
+        let decode = |e: Expr| {
+            e.reduce_with(super::Strategy::Normal, 100_000)
+                .unwrap()
+                .to_vec()
+        };
+        let numerals = |e: Expr| {
+            decode(e).map(|es| {
+                es.iter()
+                    .map(|e| e.to_numeral().unwrap())
+                    .collect::<Vec<_>>()
+            })
+        };
+        let mapped = terms::map().call(&[terms::succ(), nats(&[1, 2, 3])]);
+        assert_eq!(numerals(mapped), Some(vec![2, 3, 4]));
+        let filtered = terms::filter().call(&[terms::is_zero(), nats(&[0, 1, 0])]);
+        assert_eq!(numerals(filtered), Some(vec![0, 0]));
+        let appended = terms::append().call(&[nats(&[1]), nats(&[2, 3])]);
+        assert_eq!(numerals(appended), Some(vec![1, 2, 3]));
+        let abc = ["a", "b", "c"]
+            .iter()
+            .rev()
+            .fold(terms::nil(), |t, h| terms::cons().call(&[Expr::var(h), t]));
+        assert_eq!(
+            terms::length().apply(&abc).reduce_to_numeral(100_000),
+            Ok(3)
+        );
+        let sum = terms::foldr().call(&[terms::add(), Expr::church_zero(), nats(&[1, 2, 3])]);
+        assert_eq!(sum.reduce_to_numeral(100_000), Ok(6));
+    }
+
+    #[test]
+    fn eval_config_combinations() {
+        let default = EvalConfig::default();
+        for e in [
+            terms::and().call(&[true, false]),
+            terms::add().call(&[2_u32, 3]),
+            terms::combinators::s().apply(&terms::combinators::k()),
+            terms::map().call(&[terms::succ(), Expr::church_of_str("a").unwrap()]),
+        ] {
+            let nf = e.normalize_with(&default).unwrap();
+            assert!(nf.alpha_eq(&e.full_reduction()), "{}", e);
+        }
+        let omega = "(λx. x x) (λx. x x)".parse::<Expr>().unwrap();
+        assert_eq!(omega.normalize_with(&default), Err(EvalError::Diverged));
+        let cycles = default.detect_cycles(true);
+        assert_eq!(
+            omega.normalize_with(&cycles),
+            Err(EvalError::Cycle { step: 1 })
+        );
+
+        let power = terms::exp().call(&[2_u32, 10]);
+        let fast = default
+            .strategy(super::Strategy::Applicative)
+            .numeral_aware(true);
+        assert_eq!(
+            power.normalize_with(&fast.max_steps(3)),
+            Ok(1024_u32.to_church())
+        );
+        assert_eq!(
+            power.normalize_with(&fast.max_steps(3).max_depth(100)),
+            Err(EvalError::TooDeep { step: 1 })
+        );
+        assert_eq!(
+            power.normalize_with(&default.max_steps(3)),
+            Err(EvalError::Diverged)
+        );
+    }
+
+    #[test]
+    fn alpha_eq_explain_points_at_the_difference() {
+        let parse = |src: &str| src.parse::<Expr>().unwrap();
+        assert_eq!(parse("λx. x").alpha_eq_explain(&parse("λy. y")), Ok(()));
+        let mismatch = parse("λx. x")
+            .alpha_eq_explain(&parse("λx. x x"))
+            .unwrap_err();
+        assert_eq!(mismatch.path, vec![Dir::LamBody]);
+        assert_eq!(mismatch.description, "`x` against `x x`");
+        let mismatch = parse("λx y. x")
+            .alpha_eq_explain(&parse("λa b. b"))
+            .unwrap_err();
+        assert_eq!(mismatch.path, vec![Dir::LamBody, Dir::LamBody]);
+        assert_eq!(
+            mismatch.to_string(),
+            "at [LamBody, LamBody]: `x` against `b`"
+        );
+    }
+
+    #[test]
+    fn parse_errors_point_at_the_column() {
+        let missing_dot = "λx x".parse::<Expr>().unwrap_err();
+        assert_eq!(missing_dot.column, 4);
+        assert_eq!(
+            missing_dot.to_string(),
+            "expected '.' after binder\nλx x\n    ^"
+        );
+        let unclosed = "(λx. x".parse::<Expr>().unwrap_err();
+        assert_eq!(unclosed.to_string(), "expected ')'\n(λx. x\n      ^");
+        let program = "let id = λx. x\nlet bad = λ. x\nid".parse::<super::Program>();
+        let err = program.unwrap_err();
+        assert_eq!(err.snippet, "let bad = λ. x");
+        assert_eq!(err.column, 11);
+    }
+
+    #[test]
+    fn levels_count_from_the_root() {
+        use super::DeBrujinLevel::{App, Lam, Var};
+        let e = "λx. x (λy. x)".parse::<Expr>().unwrap();
+        let levels = Lam(Box::new(App(
+            Box::new(Var(0)),
+            Box::new(Lam(Box::new(Var(0)))),
+        )));
+        assert_eq!(e.debruijn_levels(), levels);
+        assert_eq!(e.debrujin().to_string(), "λ. 0 λ. 1");
+    }
+
+    #[test]
+    fn normalize_lazy_contracts_a_shared_argument_once() {
+        let term = "(λx. add x x) ((λexpensive. mul expensive expensive) 3)"
+            .parse::<Expr>()
+            .unwrap()
+            .substitution(&"add".to_string(), &terms::add())
+            .substitution(&"mul".to_string(), &terms::mul());
+        let is_expensive = |e: &Expr| match e {
+            Expr::App(f, _) => matches!(f.as_ref(), Expr::Lam(x, _) if x == "expensive"),
+            _ => false,
+        };
+        let mut naive = 0;
+        let mut e = term.clone();
+        while let Some(path) = e.next_redex(super::Strategy::Normal) {
+            naive += is_expensive(e.subterm(&path).unwrap()) as usize;
+            e = e.reduce_once(super::Strategy::Normal).unwrap();
+        }
+        let mut lazy = 0;
+        let nf = term.normalize_lazy_with(|x| lazy += (x == "expensive") as usize);
+        assert_eq!((naive, lazy), (2, 1));
+        assert!(nf.alpha_eq(&e));
+        assert_eq!(nf.to_numeral(), Ok(18));
+    }
+
+    #[test]
+    fn find_subterm_finds_both_identities() {
+        let parse = |src: &str| src.parse::<Expr>().unwrap();
+        let e = parse("(λx. x) ((λy. y) z)");
+        let id = parse("λx. x");
+        assert_eq!(
+            e.find_subterm(&id),
+            vec![vec![Dir::AppLeft], vec![Dir::AppRight, Dir::AppLeft]]
+        );
+        assert!(e.is_subterm(&id));
+        assert!(e.is_subterm(&parse("z")));
+        assert!(!e.is_subterm(&parse("λx. z")));
+        // `y` is bound wherever it occurs, so it is not a subterm.
+        assert!(!e.is_subterm(&parse("y")));
+    }
+
+    #[test]
+    fn rewrite_replaces_exactly_the_matches() {
+        let parse = |src: &str| src.parse::<Expr>().unwrap();
+        let id = parse("λx. x");
+        let e = parse("(λx. x) ((λy. y) z) (λz. z z)");
+        assert_eq!(
+            e.rewrite(&id, &parse("λy. y y")),
+            parse("(λy. y y) ((λy. y y) z) (λz. z z)")
+        );
+        assert_eq!(e.rewrite(&parse("w"), &id), e);
+        // The replacement is not rewritten again.
+        let twice = parse("(λx. x) (λx. x)");
+        assert_eq!(id.rewrite(&id, &twice), twice);
+        // `z` in the replacement stays free.
+        let under = parse("λz. a z");
+        assert!(under
+            .rewrite(&parse("a"), &parse("z"))
+            .alpha_eq(&parse("λw. z w")));
+    }
+
+    #[test]
+    fn eta_pattern_binds_the_function() {
+        let parse = |src: &str| src.parse::<Expr>().unwrap();
+        let eta = "λ?x. ?f ?x"
+            .parse::<Pattern>()
+            .unwrap()
+            .not_free_in("x", "f");
+        let bindings = parse("λx. g x").match_pattern(&eta).unwrap();
+        assert_eq!(bindings.get("f"), Some(&parse("g")));
+        assert_eq!(bindings.get("x"), Some(&parse("x")));
+        // `?f` would be `x`, which the side condition rules out.
+        assert_eq!(parse("λx. x x").match_pattern(&eta), None);
+        assert!(parse("λx. g y").match_pattern(&eta).is_none());
+    }
+
+    #[test]
+    fn metavariables_match_consistently() {
+        let parse = |src: &str| src.parse::<Expr>().unwrap();
+        let twice = "?a ?a".parse::<Pattern>().unwrap();
+        assert!(parse("(λx. x) λy. y").match_pattern(&twice).is_some());
+        assert_eq!(parse("a b").match_pattern(&twice), None);
+        let beta = "(λ?x. ?body) ?arg".parse::<Pattern>().unwrap();
+        let b = parse("(λy. y z) w").match_pattern(&beta).unwrap();
+        let Some(Expr::Var(x)) = b.get("x") else {
+            panic!("`?x` is a binder");
+        };
+        let contracted = b
+            .get("body")
+            .unwrap()
+            .substitution(x, b.get("arg").unwrap());
+        assert_eq!(contracted, parse("w z"));
+        // Plain names in a pattern match up to alpha, and `?x` under its
+        // binder only matches the variable it bound.
+        let k = "λa. λ?x. a".parse::<Pattern>().unwrap();
+        assert!(parse("λp. λq. p").match_pattern(&k).is_some());
+        assert!(parse("λp. λq. q").match_pattern(&k).is_none());
+        let inner = "λ?x. λy. ?x".parse::<Pattern>().unwrap();
+        assert!(parse("λy. λy. y").match_pattern(&inner).is_none());
+        assert!(parse("λx. λy. x").match_pattern(&inner).is_some());
+    }
+
+    #[test]
+    fn scott_pred_is_cheaper_than_church_pred() {
+        let church = terms::pred().apply(&8_u32.to_church());
+        let scott = super::scott::pred().apply(&super::scott::nat(8));
+        let (church_pred, church_steps) = church.normalize_counted(1_000).unwrap();
+        let (scott_pred, scott_steps) = scott.normalize_counted(1_000).unwrap();
+        assert_eq!(church_pred.to_numeral(), Ok(7));
+        assert!(scott_pred.alpha_eq(&super::scott::nat(7)));
+        // Church `pred` rebuilds all eight layers; Scott `pred` takes four
+        // steps whatever the numeral.
+        assert_eq!(scott_steps, 4);
+        assert!(church_steps > 5 * scott_steps);
+        assert_eq!(
+            8_u32.to_church().normalize_counted(10),
+            Ok((8_u32.to_church(), 0))
+        );
+        let omega = "(λx. x x) (λx. x x)".parse::<Expr>().unwrap();
+        assert_eq!(omega.normalize_counted(10), Err(EvalError::Diverged));
+    }
+
+    #[test]
+    fn alpha_eq_newtype_compares_up_to_renaming() {
+        use std::collections::HashSet;
+        let parse = |src: &str| src.parse::<Expr>().unwrap();
+        assert_ne!(parse("λx. x"), parse("λy. y"));
+        assert_eq!(AlphaEq(parse("λx. x")), AlphaEq(parse("λy. y")));
+        assert_ne!(AlphaEq(parse("λx. y")), AlphaEq(parse("λx. z")));
+        assert_ne!(AlphaEq(parse("λx. λy. x")), AlphaEq(parse("λx. λy. y")));
+        let classes: HashSet<AlphaEq> = ["λx. x", "λy. y", "λf x. f x", "λg y. g y", "a"]
+            .into_iter()
+            .map(|src| parse(src).into())
+            .collect();
+        assert_eq!(classes.len(), 3);
+    }
+
+    #[test]
+    fn builder_matches_hand_written_terms() {
+        use super::builder::term;
+        let n = |n: u32| n.to_church();
+        let sum = term().num(5).add(term().num(7)).build();
+        assert!(sum.alpha_eq(&terms::add().apply(&n(5)).apply(&n(7))));
+        assert!(sum.alpha_eq(&term().expr(terms::add()).num(5).num(7).build()));
+        let choice = term()
+            .bool(true)
+            .and(term().num(2).leq(term().num(3)))
+            .ite(term().num(1), term().num(0))
+            .build();
+        let by_hand = terms::ite()
+            .apply(
+                &terms::and()
+                    .apply(&terms::t())
+                    .apply(&terms::leq().apply(&n(2)).apply(&n(3))),
+            )
+            .apply(&n(1))
+            .apply(&n(0));
+        assert!(choice.alpha_eq(&by_hand));
+        assert_eq!(choice.reduce_to_numeral(10_000), Ok(1));
+        let list = term().list([term().num(1), term().num(2).succ()]);
+        let doubled = list.clone().map(term().expr(terms::mul().apply(&n(2))));
+        assert_eq!(
+            doubled.build().full_reduction().to_numeral_vec(),
+            Ok(vec![2, 6])
+        );
+        assert_eq!(
+            list.append(term().list([]))
+                .length()
+                .build()
+                .reduce_to_numeral(10_000),
+            Ok(2)
+        );
+    }
+
+    #[test]
+    fn latex_rendering() {
+        let parse = |src: &str| src.parse::<Expr>().unwrap();
+        let two = parse("λf x. f (f x)");
+        assert_eq!(two.to_latex(), r"\lambda f\, x.\, f\; (f\; x)");
+        assert_eq!(
+            parse("(λx. x) succ y_1").to_latex(),
+            r"(\lambda x.\, x)\; \mathit{succ}\; \mathit{y\_1}"
+        );
+        let canonical = r"\lambda x_{0}\, x_{1}.\, x_{0}\; (x_{0}\; x_{1})";
+        assert_eq!(two.to_canonical().to_latex_subscripted(), canonical);
+        assert_eq!(
+            parse("λg y. g (g y)").to_canonical().to_latex_subscripted(),
+            canonical
+        );
+        // Free variables keep their digits.
+        assert_eq!(
+            parse("λx1. x1 y2").to_latex_subscripted(),
+            r"\lambda x_{1}.\, x_{1}\; \mathit{y2}"
+        );
+    }
+
+    #[test]
+    fn custom_selector_reaches_the_same_normal_form() {
+        use super::RedexSelector;
+        // The last redex in pre-order: rightmost, and innermost among those.
+        struct Rightmost;
+        impl RedexSelector for Rightmost {
+            fn select(&self, e: &Expr) -> Option<super::Path> {
+                e.redex_paths().pop()
+            }
+        }
+        let cfg = EvalConfig::default().max_steps(1_000);
+        let e = terms::mul().call(&[2_u32, 3]);
+        let expected = e.normalize_by(&super::Strategy::Normal, &cfg).unwrap();
+        assert_eq!(
+            e.normalize_by(&Rightmost, &cfg).unwrap().to_numeral(),
+            Ok(6)
+        );
+        assert!(e
+            .normalize_by(&Rightmost, &cfg)
+            .unwrap()
+            .alpha_eq(&expected));
+        let nested = "(λx. x) ((λy. y) z)".parse::<Expr>().unwrap();
+        assert_eq!(
+            nested.reduce_once_by(&Rightmost),
+            Some("(λx. x) z".parse().unwrap())
+        );
+        let omega = "(λx. x x) (λx. x x)".parse::<Expr>().unwrap();
+        let cfg = cfg.max_steps(50);
+        assert_eq!(
+            omega.normalize_by(&Rightmost, &cfg),
+            Err(EvalError::Diverged)
+        );
+    }
+
+    #[test]
+    fn selector_picking_a_non_redex_is_an_error() {
+        use super::RedexSelector;
+        // Always the root, which is a redex only until the first step.
+        struct Root;
+        impl RedexSelector for Root {
+            fn select(&self, _: &Expr) -> Option<super::Path> {
+                Some(vec![])
+            }
+        }
+        let cfg = EvalConfig::default();
+        let e = "(λx. x) (λy. (λz. z) y)".parse::<Expr>().unwrap();
+        assert_eq!(
+            e.normalize_by(&Root, &cfg),
+            Err(EvalError::BadSelector { step: 1 })
+        );
+        let var = "x".parse::<Expr>().unwrap();
+        assert_eq!(
+            var.normalize_by(&Root, &cfg),
+            Err(EvalError::BadSelector { step: 0 })
+        );
+    }
+
+    #[test]
+    fn weak_selectors_stop_at_lambdas() {
+        let parse = |src: &str| src.parse::<Expr>().unwrap();
+        let cfg = EvalConfig::default().max_steps(50);
+        let e = parse("(λx. λy. (λz. z) x) a");
+        for selector in [&ByName as &dyn super::RedexSelector, &ByValue] {
+            let weak = e.normalize_by(selector, &cfg).unwrap();
+            assert!(weak.alpha_eq(&parse("λy. (λz. z) a")));
+        }
+        // Arguments of a variable are still reduced.
+        assert_eq!(
+            parse("x ((λy. y) z)").reduce_once_by(&ByName),
+            Some(parse("x z"))
+        );
+        // By name the unused argument is dropped; by value it is reduced
+        // first, forever.
+        let e = parse("(λx. y) ((λx. x x) (λx. x x))");
+        assert_eq!(e.normalize_by(&ByName, &cfg), Ok(parse("y")));
+        assert_eq!(e.normalize_by(&ByValue, &cfg), Err(EvalError::Diverged));
+        assert_eq!(
+            parse("(λx. x) ((λy. y) z)").reduce_once_by(&ByValue),
+            Some(parse("(λx. x) z"))
+        );
+        // The other options of the config apply too.
+        let sum = terms::add().call(&[2_u32, 3]);
+        let weak = sum.normalize_by(&ByName, &cfg).unwrap();
+        assert!(!weak.alpha_eq(&5_u32.to_church()));
+        let computed = sum.normalize_by(&ByName, &cfg.numeral_aware(true));
+        assert_eq!(computed, Ok(5_u32.to_church()));
+        let cycles = cfg.detect_cycles(true);
+        let omega = parse("(λx. x x) (λx. x x)");
+        assert_eq!(
+            omega.normalize_by(&ByName, &cycles),
+            Err(EvalError::Cycle { step: 1 })
+        );
+    }
+
+    #[test]
+    fn church_sum_and_product_fold_the_terms() {
+        let decode = |e: ChurchExpr| e.0.reduce_to_numeral(10_000);
+        let product: ChurchExpr = [1, 2, 3].into_iter().map(Church).product();
+        assert!(product.0.alpha_eq(&terms::applied(
+            terms::mul(),
+            [terms::mul().call(&[1_u32, 2]), 3_u32.to_church()]
+        )));
+        assert_eq!(decode(product), Ok(6));
+        assert_eq!(decode([4, 5, 6].into_iter().map(Church).sum()), Ok(15));
+        assert_eq!(decode(core::iter::empty::<Church>().sum()), Ok(0));
+        assert_eq!(decode(core::iter::empty::<Church>().product()), Ok(1));
+        assert_eq!(decode((Church(2) + Church(3)) * Church(4)), Ok(20));
+    }
+
+    #[test]
+    fn observer_sees_every_contraction() {
+        let e = terms::mul()
+            .apply(&terms::add().call(&[1_u32, 2]))
+            .apply(&2_u32.to_church());
+        let (_, steps) = e.normalize_counted(10_000).unwrap();
+        let mut calls = 0;
+        let normal = e
+            .normalize_observed(&EvalConfig::default(), &mut |current, path| {
+                calls += 1;
+                assert!(current.redex_paths().contains(path));
+            })
+            .unwrap();
+        assert_eq!(calls, steps);
+        assert_eq!(normal.to_numeral(), Ok(6));
+        let mut arithmetic = 0;
+        let cfg = EvalConfig::default().numeral_aware(true);
+        e.normalize_observed(&cfg, &mut |_, _| arithmetic += 1)
+            .unwrap();
+        assert!(arithmetic < calls);
+    }
+
+    #[test]
+    fn to_numeral_agrees_across_reducers() {
+        let e = terms::mul().call(&[2_u32, 3]);
+        assert_eq!(e.normalize_nbe().to_numeral(), Ok(6));
+        assert_eq!(e.full_reduction().to_numeral(), Ok(6));
+        assert_eq!(e.normalize_lazy().to_numeral(), Ok(6));
+        // Not in normal form, but a numeral up to beta.
+        assert_eq!(e.to_numeral(), Ok(6));
+        let shaped = "λf x. (λy. f y) ((λg. g) (f x))".parse::<Expr>().unwrap();
+        assert_eq!(shaped.to_numeral(), Ok(2));
+        assert!("λf x. f f x".parse::<Expr>().unwrap().to_numeral().is_err());
+        let omega = "(λx. x x) (λx. x x)".parse::<Expr>().unwrap();
+        assert!(omega.to_numeral().is_err());
+    }
+
+    #[test]
+    fn free_var_paths_skip_bound_occurrences() {
+        use Dir::*;
+        let e = "λy. x (λx. x) x".parse::<Expr>().unwrap();
+        let paths = e.free_var_paths("x");
+        assert_eq!(
+            paths,
+            vec![vec![LamBody, AppLeft, AppLeft], vec![LamBody, AppRight]]
+        );
+        assert_eq!(paths.len(), e.free_occurrence_count("x"));
+        assert!(paths
+            .iter()
+            .all(|path| e.subterm(path) == Some(&Expr::var("x"))));
+        assert!(e.free_var_paths("y").is_empty());
+    }
+
+    #[test]
+    fn map_binders_renames_without_capture() {
+        let parse = |src: &str| src.parse::<Expr>().unwrap();
+        let e = parse("λx. λy. x y");
+        assert_eq!(
+            e.map_binders(|id| format!("m_{id}")),
+            parse("λm_x. λm_y. m_x m_y")
+        );
+        let collided = e.map_binders(|_| "z".to_string());
+        assert_eq!(collided, parse("λz. λz'. z z'"));
+        assert!(collided.alpha_eq(&e));
+        // `y` is free, so no binder may become `y`; `x` stays free too.
+        let open = parse("λa. a y x");
+        let renamed = open.map_binders(|_| "y".to_string());
+        assert!(renamed.alpha_eq(&open));
+        assert_eq!(renamed.fv(), open.fv());
+    }
+
+    #[test]
+    fn beta_eta_normal_forms() {
+        let parse = |src: &str| src.parse::<Expr>().unwrap();
+        let e = parse("λx. (g y) x");
+        assert!(e.is_normal());
+        assert!(!e.is_eta_normal());
+        assert_eq!(e.beta_eta_normalize(100), Ok(parse("g y")));
+        assert!(parse("λx. x x").is_eta_normal());
+        assert!(parse("λx. g x x").is_eta_normal());
+        // The redex only shows up after beta.
+        let hidden = parse("λx. (λh. h x) f");
+        assert!(hidden.is_eta_normal());
+        assert_eq!(hidden.beta_eta_normalize(100), Ok(parse("f")));
+        let omega = parse("(λx. x x) (λx. x x)");
+        assert_eq!(omega.beta_eta_normalize(100), Err(EvalError::Diverged));
+    }
+
+    #[test]
+    fn church_add_and_mul_compute_literals_directly() {
+        let sum = terms::church_add(&5_u32.to_church(), &7_u32.to_church());
+        assert!(sum.is_normal());
+        assert!(sum.alpha_eq(&12_u32.to_church()));
+        let product = terms::church_mul(&sum, &3_u32.to_church());
+        assert!(product.alpha_eq(&36_u32.to_church()));
+        let x = Expr::var("x");
+        assert_eq!(
+            terms::church_add(&x, &2_u32.to_church()),
+            terms::add().apply(&x).apply(&2_u32.to_church())
+        );
+        // Not a literal numeral until reduced, so it is left to the reducer.
+        let pending = terms::succ().apply(&1_u32.to_church());
+        let built = terms::church_mul(&pending, &2_u32.to_church());
+        assert_eq!(
+            built,
+            terms::mul().apply(&pending).apply(&2_u32.to_church())
+        );
+        assert_eq!(built.reduce_to_numeral(1_000), Ok(4));
+    }
+
+    #[test]
+    fn church_fast_reduces_to_its_value() {
+        for n in [0_u32, 1, 2, 5, 8, 13] {
+            assert_eq!(terms::church_fast(n).reduce_to_numeral(10_000), Ok(n));
+        }
+        let fast = terms::church_fast(1000);
+        assert!(fast.depth() < 100);
+        assert!(1000_u32.to_church().depth() > 1000);
+        assert_eq!(fast.reduce_to_numeral(100_000), Ok(1000));
+        let cfg = EvalConfig::default().max_steps(20).numeral_aware(true);
+        assert_eq!(
+            fast.normalize_with(&cfg).map(|e| e.to_numeral()),
+            Ok(Ok(1000))
+        );
+    }
+
+    #[test]
+    fn eta_long_expands_every_occurrence() {
+        let parse = |src: &str| src.parse::<Expr>().unwrap();
+        assert!(Expr::var("f").eta_long(1).alpha_eq(&parse("λx. f x")));
+        assert!(parse("λf x. g f (f x)")
+            .eta_long(0)
+            .alpha_eq(&parse("λf x. g (λy. f y) (f x)")));
+        assert!(terms::succ()
+            .eta_long(2)
+            .alpha_eq(&parse("λn f x. f (n (λy. f y) x)")));
+        // Already long enough: only the inner `f` changes.
+        assert!(terms::succ()
+            .eta_long(4)
+            .alpha_eq(&parse("λn f x y. f (n (λz. f z) x) y")));
+    }
+
+    #[test]
+    fn max_live_binders_matches_a_hand_count() {
+        let parse = |src: &str| src.parse::<Expr>().unwrap();
+        // Under `d`, the binders `a`, `b` and `d` are referenced; `c` is not.
+        let deep = parse("λa b c d. a (b d)");
+        assert_eq!(deep.max_live_binders(), 3);
+        // The outer `x` is shadowed, so it cannot be referenced.
+        assert_eq!(parse("λx. λx. x").max_live_binders(), 1);
+        let picked = terms::ite()
+            .apply(&terms::t())
+            .apply(&deep)
+            .apply(&Expr::church_zero());
+        let (normal, live) = picked.full_reduction_live(3).unwrap();
+        assert!(normal.alpha_eq(&deep));
+        assert_eq!(live, 3);
+        assert_eq!(deep.full_reduction_live(2), None);
+    }
+
+    #[test]
+    fn bool_lists_decode() {
+        let bits = terms::cons().apply_all(&[
+            terms::t(),
+            terms::cons().apply_all(&[terms::f(), terms::nil()]),
+        ]);
+        assert_eq!(bits.to_bool_vec(), Some(vec![true, false]));
+        assert_eq!(terms::nil().to_bool_vec(), Some(vec![]));
+        let mixed = terms::cons().apply_all(&[3_u32.to_church(), terms::nil()]);
+        assert_eq!(mixed.to_bool_vec(), None);
+    }
+
+    #[test]
+    fn substitution_renames_a_capturing_binder() {
+        let parse = |src: &str| src.parse::<Expr>().unwrap();
+        let x = "x".to_string();
+        let renamed = parse("λy. x y").substitution(&x, &Expr::var("y"));
+        assert!(renamed.alpha_eq(&parse("λz. y z")));
+        let nested = parse("λy. λz. x y z").substitution(&x, &parse("y z"));
+        assert!(nested.alpha_eq(&parse("λa. λb. y z a b")));
+    }
+
+    #[test]
+    fn exp_and_comparisons_decode() {
+        let exp = terms::exp().call(&[2_u32, 10]);
+        assert!(exp
+            .full_reduction()
+            .exact_equivalence(&1024_u32.to_church()));
+        let truth = |op: Expr, m: u32, n: u32| op.call(&[m, n]).full_reduction().to_bool();
+        for (m, n) in [(3, 5), (5, 5), (5, 3)] {
+            assert_eq!(truth(terms::leq(), m, n), Some(m <= n));
+            assert_eq!(truth(terms::lt(), m, n), Some(m < n));
+            assert_eq!(truth(terms::eq_nat(), m, n), Some(m == n));
+        }
+    }
+
+    #[test]
+    fn partial_apply_specializes_add() {
+        let add_two = terms::add().partial_apply(&[2_u32.to_church()]);
+        let expected = "λm f x. (λf x. f (f x)) f (m f x)".parse::<Expr>().unwrap();
+        assert!(add_two.alpha_eq(&expected));
+        let five = add_two.apply(&3_u32.to_church());
+        assert_eq!(five.equivalence(&5_u32.to_church()), Some(true));
+    }
+
+    #[test]
+    fn render_prints_each_notation() {
+        let t = "λx. λy. x".parse::<Expr>().unwrap();
+        assert_eq!(t.render(Notation::Named), "λx y. x");
+        assert_eq!(t.render(Notation::DeBruijn), "λ. λ. 1");
+        assert_eq!(t.render(Notation::Both), "λx y. x\nλ. λ. 1");
+    }
+
+    #[test]
+    fn nbe_agrees_with_full_reduction_on_arithmetic() {
+        let small = [
+            terms::add().call(&[2_u32, 3]),
+            terms::mul().apply_all(&[terms::mul().call(&[2_u32, 2]), 3_u32.to_church()]),
+            terms::pred().call(&[4_u32]),
+            terms::sub().call(&[5_u32, 2]),
+        ];
+        for e in &small {
+            assert!(e.normalize_nbe().exact_equivalence(&e.full_reduction()));
+        }
+        let exp = terms::exp().call(&[2_u32, 10]);
+        assert_eq!(exp.normalize_nbe().to_numeral(), Ok(1024));
+        let square = terms::mul().call(&[12_u32, 12]);
+        assert_eq!(square.normalize_nbe().to_numeral(), Ok(144));
+    }
+
+    #[test]
+    fn debrujin_binds_shadowed_names_innermost() {
+        let parse = |src: &str| src.parse::<Expr>().unwrap();
+        assert_eq!(parse("λx. λx. x").debrujin().to_string(), "λ. λ. 0");
+        assert_eq!(
+            parse("λx. λy. λx. y x").debrujin().to_string(),
+            "λ. λ. λ. 1 0"
+        );
+        assert!(!parse("λx. λx. x").alpha_eq(&terms::t()));
+        assert!(parse("λx. λx. x").alpha_eq(&terms::f()));
+    }
+
+    #[test]
+    fn krivine_agrees_with_whnf() {
+        let omega = "(λx. x x) (λx. x x)".parse::<Expr>().unwrap();
+        let lazy = terms::t().apply(&terms::t()).apply(&omega);
+        let pair = terms::tuple(&2_u32.to_church(), &terms::mul().call(&[2_u32, 3]));
+        for e in [
+            lazy,
+            terms::succ().call(&[0_u32]),
+            terms::mul().call(&[2_u32, 3]),
+            terms::first().apply(&pair),
+        ] {
+            assert!(e.krivine_whnf().exact_equivalence(&e.whnf()), "{e}");
+        }
+        assert!(terms::t()
+            .apply(&terms::t())
+            .apply(&omega)
+            .krivine_whnf()
+            .alpha_eq(&terms::t()));
+    }
+
+    #[test]
+    fn beta_reduce_at_contracts_each_redex() {
+        let parse = |src: &str| src.parse::<Expr>().unwrap();
+        let nested = parse("(λx. x) ((λy. y) z)");
+        assert_eq!(nested.redex_paths(), vec![vec![], vec![Dir::AppRight]]);
+        assert_eq!(nested.beta_reduce_at(&vec![]), Ok(parse("(λy. y) z")));
+        assert_eq!(
+            nested.beta_reduce_at(&vec![Dir::AppRight]),
+            Ok(parse("(λx. x) z"))
+        );
+        assert_eq!(
+            nested.beta_reduce_at(&vec![Dir::AppLeft]),
+            Err(NotARedex(vec![Dir::AppLeft]))
+        );
+    }
+
+    #[test]
+    fn would_capture_reports_the_capturing_binder() {
+        let parse = |src: &str| src.parse::<Expr>().unwrap();
+        let x = "x".to_string();
+        assert_eq!(parse("λy. x").would_capture(&x, &Expr::var("y")), vec!["y"]);
+        assert!(parse("λz. x").would_capture(&x, &Expr::var("y")).is_empty());
+        // `x` is bound, so nothing is substituted and nothing captured.
+        assert!(parse("λy. λx. x")
+            .would_capture(&x, &Expr::var("y"))
+            .is_empty());
+    }
+
+    #[test]
+    fn fingerprints_tell_small_terms_apart() {
+        let parse = |src: &str| src.parse::<Expr>().unwrap();
+        assert_eq!(parse("λx. x").fingerprint(), parse("λy. y").fingerprint());
+        assert_ne!(terms::t().fingerprint(), terms::f().fingerprint());
+        let mut numerals: Vec<u64> = (0..8_u32).map(|n| n.to_church().fingerprint()).collect();
+        numerals.sort_unstable();
+        numerals.dedup();
+        assert_eq!(numerals.len(), 8);
+    }
+
+    #[test]
+    fn dot_declares_every_node() {
+        let check = |dot: &str, nodes: usize, dashed: usize| {
+            assert!(dot.starts_with("digraph {\n") && dot.ends_with("}\n"));
+            let declared: Vec<&str> = dot
+                .lines()
+                .filter(|l| l.contains("[label="))
+                .map(|l| l.trim().split(' ').next().unwrap())
+                .collect();
+            assert_eq!(declared.len(), nodes);
+            for edge in dot.lines().filter(|l| l.contains("->")) {
+                let ends: Vec<&str> = edge.trim().trim_end_matches(';').split(" -> ").collect();
+                let target = ends[1].split(' ').next().unwrap();
+                assert!(declared.contains(&ends[0]) && declared.contains(&target));
+            }
+            assert_eq!(dot.matches("style=dashed").count(), dashed);
+        };
+        check(&terms::t().to_dot(), 3, 0);
+        let nested = "(λx. x) ((λy. y) z)".parse::<Expr>().unwrap();
+        check(&nested.to_dot(), 7, 0);
+        check(&nested.to_dot_linked(), 7, 2);
+    }
+
+    #[test]
+    fn deadline_stops_an_unfinished_reduction() {
+        use std::time::{Duration, Instant};
+        let omega = "(λx. x x) (λx. x x)".parse::<Expr>().unwrap();
+        let (unfinished, done) = omega.reduce_until_deadline(Instant::now());
+        assert!(!done);
+        assert!(unfinished.alpha_eq(&omega));
+        let later = Instant::now() + Duration::from_secs(10);
+        let (normal, done) = terms::succ().call(&[0_u32]).reduce_until_deadline(later);
+        assert!(done);
+        assert!(normal.alpha_eq(&Expr::church_one()));
+    }
+
+    #[test]
+    fn scott_numerals_round_trip_and_pred_is_constant() {
+        for n in 0..10 {
+            assert_eq!(scott::nat(n).scott_to_u32(), Ok(n));
+        }
+        let pred = |n| scott::pred().apply(&scott::nat(n));
+        assert_eq!(pred(5).redex_paths().len(), 1);
+        let steps = |n| pred(n).normalize_counted(100).unwrap().1;
+        assert_eq!(steps(5), steps(50));
+        assert_eq!(pred(50).full_reduction().scott_to_u32(), Ok(49));
+        let is_zero = |n| {
+            scott::case()
+                .apply(&scott::nat(n))
+                .apply(&terms::t())
+                .apply(&Expr::lambda("p", terms::f()))
+                .full_reduction()
+                .to_bool()
+        };
+        assert_eq!((is_zero(0), is_zero(3)), (Some(true), Some(false)));
+        let head = scott::cons()
+            .apply(&scott::nat(5))
+            .apply(&scott::nil())
+            .apply(&scott::nil())
+            .apply(&terms::t());
+        assert_eq!(head.full_reduction().scott_to_u32(), Ok(5));
+    }
+
+    #[test]
+    fn sums_decode_both_injections() {
+        let one = Expr::church_one();
+        let inl = terms::sum::left().apply(&one);
+        let inr = terms::sum::right().apply(&terms::t());
+        assert_eq!(inl.full_reduction().to_either(), Some(Ok(one.clone())));
+        assert_eq!(inr.full_reduction().to_either(), Some(Err(terms::t())));
+        let case = |sum: &Expr| {
+            terms::sum::case()
+                .apply(sum)
+                .apply(&terms::succ())
+                .apply(&terms::not())
+        };
+        assert_eq!(case(&inl).equivalence(&2_u32.to_church()), Some(true));
+        assert_eq!(case(&inr).equivalence(&terms::f()), Some(true));
+    }
+
+    #[test]
+    fn detect_omega_finds_self_reproducing_redexes() {
+        let parse = |src: &str| src.parse::<Expr>().unwrap();
+        let omega = parse("(λx. x x) (λx. x x)");
+        for e in [
+            omega.clone(),
+            omega.apply(&Expr::church_one()),
+            parse("(λx. x x x) (λx. x x x)"),
+        ] {
+            assert!(e.detect_omega(), "{e}");
+        }
+        let lazy = terms::t().apply(&terms::t()).apply(&omega);
+        for e in [
+            lazy,
+            terms::succ().call(&[0_u32]),
+            terms::add().call(&[2_u32, 3]),
+            terms::mul().call(&[2_u32, 3]),
+            terms::exp().call(&[2_u32, 3]),
+            terms::pred().call(&[3_u32]),
+            terms::sub().call(&[5_u32, 2]),
+        ] {
+            assert!(!e.detect_omega(), "{e}");
+        }
+    }
+
+    #[test]
+    fn variable_inspection_on_a_mixed_term() {
+        let e = "λx. λy. x z".parse::<Expr>().unwrap();
+        assert_eq!(e.bound_variables(), ["x", "y"]);
+        assert_eq!(e.fv(), ["z"]);
+        assert_eq!(e.all_variables(), ["x", "y", "z"]);
+    }
+
+    #[test]
+    fn common_prefix_puts_a_hole_where_terms_differ() {
+        let parse = |src: &str| src.parse::<Expr>().unwrap();
+        let prefix = parse("λx. f a").common_prefix(&parse("λx. f b"));
+        assert_eq!(prefix.to_string(), "λx. f _");
+        assert_eq!(
+            parse("λx. x").common_prefix(&parse("λx. x")),
+            parse("λx. x")
+        );
+    }
+
+    #[test]
+    fn normalize_modulo_unfolds_only_heads() {
+        let mut defs = super::Env::new();
+        defs.insert("two".to_string(), 2_u32.to_church());
+        defs.insert("succ".to_string(), terms::succ());
+        let argument = terms::t().apply(&Expr::var("two")).normalize_modulo(&defs);
+        assert!(argument.alpha_eq(&Expr::lambda("y", Expr::var("two"))));
+        let head = Expr::var("succ")
+            .apply(&Expr::var("two"))
+            .normalize_modulo(&defs);
+        assert!(head.alpha_eq(&3_u32.to_church()));
+    }
+
+    #[test]
+    fn to_canonical_renames_binders_only() {
+        let parse = |src: &str| src.parse::<Expr>().unwrap();
+        let canonical = parse("λx0. x0");
+        assert_eq!(parse("λa. a").to_canonical(), canonical);
+        assert_eq!(parse("λb. b").to_canonical(), canonical);
+        assert_eq!(parse("λx. λy. x z").to_canonical(), parse("λx0. λx1. x0 z"));
+        // A free `x0` is skipped.
+        assert_eq!(parse("λa. a x0").to_canonical(), parse("λx1. x1 x0"));
+    }
+
+    #[test]
+    fn applied_builds_a_conditional() {
+        let conditional = terms::applied(terms::ite(), [&true as &dyn ToLambda, &1_u32, &2_u32]);
+        assert_eq!(conditional.reduce_to_numeral(100), Ok(1));
+        let sum = terms::applied(terms::add(), [2_u32, 3]);
+        assert_eq!(sum.reduce_to_numeral(100), Ok(5));
+    }
+
+    #[test]
+    fn chained_lets_normalize() {
+        let program: super::Program = "let two = λf x. f (f x)
+            let succ = λn f x. f (n f x)
+            let three = succ two
+            three"
+            .parse()
+            .unwrap();
+        let expr = program.to_expr().unwrap();
+        assert_eq!(expr.reduce_to_numeral(100), Ok(3));
+        let inline = "let x = λa. a in x x".parse::<Expr>().unwrap();
+        assert!(inline.full_reduction().alpha_eq(&"λa. a".parse().unwrap()));
+    }
+
+    #[test]
+    fn de_bruijn_normalize_agrees_with_full_reduction() {
+        for e in [
+            terms::and().call(&[true, false]),
+            terms::not().call(&[true]),
+            terms::succ().call(&[3_u32]),
+            terms::add().call(&[2_u32, 3]),
+            terms::mul().call(&[2_u32, 3]),
+            terms::exp().call(&[2_u32, 3]),
+            terms::pred().call(&[3_u32]),
+            terms::sub().call(&[5_u32, 2]),
+            terms::leq().call(&[2_u32, 3]),
+        ] {
+            assert_eq!(
+                e.full_reduction().debrujin(),
+                e.debrujin().normalize(),
+                "{e}"
+            );
+        }
+    }
+
+    #[test]
+    fn collapsing_merges_nested_binders() {
+        let two = 2_u32.to_church();
+        assert_eq!(two.to_string_styled(PrintStyle::UNICODE), "λf. λx. f (f x)");
+        assert_eq!(
+            two.to_string_styled(PrintStyle::UNICODE | PrintStyle::COLLAPSE),
+            "λf x. f (f x)"
+        );
+        let add = terms::add();
+        assert_eq!(
+            add.to_string_styled(PrintStyle::ASCII),
+            "\\n. \\m. \\f. \\x. n f (m f x)"
+        );
+        assert_eq!(
+            add.to_string_styled(PrintStyle::ASCII | PrintStyle::COLLAPSE),
+            "\\n m f x. n f (m f x)"
+        );
+        assert_eq!(add.to_string().parse::<Expr>(), Ok(add));
+    }
+
+    #[test]
+    fn numeral_lists_decode() {
+        let digits = [3_u32, 1, 4].iter().rev().fold(terms::nil(), |acc, n| {
+            terms::cons().apply_all_owned(vec![n.to_church(), acc])
+        });
+        assert_eq!(digits.to_numeral_vec(), Ok(vec![3, 1, 4]));
+        let mixed = terms::cons().apply_all_owned(vec![
+            3_u32.to_church(),
+            terms::cons().apply_all(&[terms::t(), terms::nil()]),
+        ]);
+        assert_eq!(mixed.to_numeral_vec(), Err(NotANumeralList::Element(1)));
+        assert_eq!(terms::t().to_numeral_vec(), Err(NotANumeralList::NotAList));
+    }
+
+    #[test]
+    fn integers_decode_with_sign() {
+        use super::integers::{add_int, neg, of_int, sub_int};
+        assert_eq!(sub_int().call(&[of_int(3), of_int(5)]).to_i32(), Ok(-2));
+        assert_eq!(add_int().call(&[of_int(-4), of_int(9)]).to_i32(), Ok(5));
+        let zero = neg().apply(&sub_int().call(&[of_int(2), of_int(2)]));
+        assert_eq!(zero.to_i32(), Ok(0));
+        for i in [-3, 0, 7] {
+            assert_eq!(of_int(i).to_i32(), Ok(i));
+        }
+    }
+
+    #[test]
+    fn deep_terms_stay_off_the_stack() {
+        // Deep enough to overflow a test thread when these recursed, and
+        // shallow enough for the derived, recursive `Drop`.
+        const DEPTH: usize = 10_000;
+        // `x (x (... ((λz. z) y)))`, nested to the right.
+        let chain = (0..DEPTH).fold(
+            Expr::lambda("z", Expr::var("z")).apply(&Expr::var("y")),
+            |acc, _| Expr::App(Box::new(Expr::var("x")), Box::new(acc)),
+        );
+        let reduced = chain.reduction();
+        assert_eq!(reduced.fv().len(), DEPTH + 1);
+        let substituted = reduced.substitution(&"y".to_string(), &Expr::var("w"));
+        let free = substituted.fv();
+        assert!(free.contains(&"w".to_string()) && !free.contains(&"y".to_string()));
+        // `f a a ... a`, nested to the left.
+        let spine = (0..DEPTH).fold(Expr::var("f"), |acc, _| {
+            Expr::App(Box::new(acc), Box::new(Expr::var("a")))
+        });
+        let (head, args) = spine.to_app_vec();
+        assert_eq!((head, args.len()), (&Expr::var("f"), DEPTH));
+    }
+
+    #[test]
+    fn equivalent_within_gives_up_on_divergence() {
+        let five = 5_u32.to_church();
+        let sum = terms::add().call(&[2_u32, 3]);
+        assert_eq!(
+            sum.equivalent_within(&five, 100, super::Strategy::Normal),
+            Some(true)
+        );
+        assert_eq!(
+            sum.equivalent_within(&6_u32.to_church(), 100, super::Strategy::Normal),
+            Some(false)
+        );
+        let omega = "(λx. x x) (λx. x x)".parse::<Expr>().unwrap();
+        assert_eq!(
+            omega.equivalent_within(&five, 100, super::Strategy::Normal),
+            None
+        );
+        assert_eq!(
+            five.equivalent_within(&omega, 100, super::Strategy::Normal),
+            None
+        );
+    }
+
+    #[test]
+    fn fresh_supplies_are_deterministic_across_threads() {
+        let capturing = Expr::lambda("y", terms::t().apply(&Expr::var("y")));
+        let reduce = || capturing.full_reduction_with(&mut FreshSupply::new());
+        let expected = reduce();
+        assert_eq!(expected.to_string(), "λy v1. y");
+        let from_threads: Vec<Expr> = std::thread::scope(|s| {
+            let handles: Vec<_> = (0..2).map(|_| s.spawn(reduce)).collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        assert_eq!(from_threads, [expected.clone(), expected]);
+    }
+
+    #[test]
+    fn cmp_numeral_orders_by_value() {
+        use core::cmp::Ordering;
+        let (three, five) = (3_u32.to_church(), 5_u32.to_church());
+        assert_eq!(three.cmp_numeral(&five), Ok(Ordering::Less));
+        assert_eq!(five.cmp_numeral(&three), Ok(Ordering::Greater));
+        let sum = terms::add().call(&[2_u32, 3]);
+        assert_eq!(sum.cmp_numeral(&five), Ok(Ordering::Equal));
+        assert_eq!(three.cmp_numeral(&terms::t()), Err(NotANumeral));
+    }
+
+    #[test]
+    fn annotations_match_binders_to_occurrences() {
+        let e = "λx. x y".parse::<Expr>().unwrap();
+        let rendered = e.to_string();
+        let tokens: Vec<(&str, TokenKind)> = e
+            .to_annotated()
+            .into_iter()
+            .map(|(span, kind)| (&rendered[span], kind))
+            .collect();
+        assert_eq!(
+            tokens,
+            [
+                ("λ", TokenKind::Lambda),
+                ("x", TokenKind::Binder(0)),
+                (".", TokenKind::Dot),
+                ("x", TokenKind::Bound(0)),
+                ("y", TokenKind::Free),
+            ]
+        );
+    }
+
+    #[test]
+    fn occurs_free_skips_shadowed_occurrences() {
+        let e = "λy. x (λx. x) x".parse::<Expr>().unwrap();
+        assert!(e.occurs_free("x"));
+        assert_eq!(e.free_occurrence_count("x"), 2);
+        assert!(!e.occurs_free("y"));
+        assert_eq!(e.free_occurrence_count("y"), 0);
+    }
+
+    #[test]
+    fn to_church_works_for_every_unsigned_type() {
+        let parse = |src: &str| src.parse::<Expr>().unwrap();
+        assert!(3_u8.to_church().alpha_eq(&parse("λs z. s (s (s z))")));
+        assert!(2_u64.to_church().alpha_eq(&parse("λf x. f (f x)")));
+        assert!(0_u16.to_church().alpha_eq(&parse("λf x. x")));
+        assert_eq!(255_u8.to_church().to_numeral(), Ok(255));
+    }
+
+    #[test]
+    fn builtin_resolves_library_names() {
+        assert_eq!(terms::builtin("succ"), Some(terms::succ()));
+        assert_eq!(terms::builtin("frobnicate"), None);
+        let b = |name| terms::builtin(name).unwrap();
+        let skk = b("S").apply(&b("K")).apply(&b("K"));
+        assert!(skk.normalize_nbe().alpha_eq(&b("I")));
+    }
+
+    #[test]
+    fn reduce_once_steps_to_the_normal_form() {
+        let parse = |src: &str| src.parse::<Expr>().unwrap();
+        let e = parse("(λx. x) ((λy. y) z)");
+        let once = e.reduce_once(super::Strategy::Normal).unwrap();
+        assert_eq!(once, parse("(λy. y) z"));
+        let steps: Vec<Expr> = e.reduction_steps(super::Strategy::Normal).collect();
+        assert_eq!(steps, [parse("(λy. y) z"), parse("z")]);
+        assert_eq!(Expr::var("z").reduce_once(super::Strategy::Normal), None);
+    }
+
+    #[test]
+    fn renamed_numerals_still_decode() {
+        let renamed = terms::exp().call(&[2_u32, 2]).full_reduction();
+        assert_eq!(renamed.bound_variables(), ["x", "x''"]);
+        assert_eq!(renamed.to_numeral(), Ok(4));
+    }
+
+    #[test]
+    fn subst_many_is_simultaneous() {
+        let parse = |src: &str| src.parse::<Expr>().unwrap();
+        let swap = [
+            ("x".to_string(), Expr::var("y")),
+            ("y".to_string(), Expr::var("z")),
+        ];
+        let xy = parse("x y");
+        assert_eq!(xy.subst_many(&swap), parse("y z"));
+        let sequential = swap
+            .iter()
+            .fold(xy.clone(), |acc, (id, e)| acc.substitution(id, e));
+        assert_eq!(sequential, parse("z z"));
+        let under = Expr::lambda("y", xy).subst_many(&swap);
+        assert!(under.alpha_eq(&parse("λw. y w")));
+    }
+
+    #[test]
+    fn normalizing_twice_prints_identically() {
+        let normalize = || terms::exp().call(&[3_u32, 2]).full_reduction().to_string();
+        assert_eq!(normalize(), normalize());
+        assert_eq!(normalize(), "λx x''. x (x (x (x (x (x (x (x (x x''))))))))");
+    }
+
+    #[test]
+    fn rename_and_map_free_vars_avoid_capture() {
+        let parse = |src: &str| src.parse::<Expr>().unwrap();
+        let renamed = parse("λy. x").rename("x", "y");
+        assert!(renamed.alpha_eq(&parse("λw. y")));
+        assert!(renamed.occurs_free("y"));
+        let opened = parse("λx. x y (λy. y z)").map_free_vars(|_| Expr::church_zero());
+        let zero = Expr::church_zero();
+        let expected = Expr::lambda(
+            "x",
+            Expr::var("x")
+                .apply(&zero)
+                .apply(&Expr::lambda("y", Expr::var("y").apply(&zero))),
+        );
+        assert!(opened.alpha_eq(&expected));
+    }
+
+    #[test]
+    fn simply_typed_checking() {
+        use super::stlc::{Type, TypeCtx, TypeError};
+        let parse = |src: &str| src.parse::<Expr>().unwrap();
+        let a = Type::base("a");
+        let assume_x: TypeCtx = vec![("x".to_string(), a.clone())];
+        let id = parse("λx. x").type_check(&assume_x).unwrap();
+        assert_eq!(id, Type::arrow(a.clone(), a.clone()));
+        assert!(matches!(
+            parse("λx. x x").type_check(&assume_x),
+            Err(TypeError::NotAFunction { .. })
+        ));
+        let apply_f: TypeCtx = vec![
+            ("f".to_string(), Type::arrow(a.clone(), a.clone())),
+            ("y".to_string(), Type::base("b")),
+        ];
+        assert_eq!(
+            parse("f y").type_check(&apply_f),
+            Err(TypeError::Mismatch {
+                path: vec![Dir::AppRight],
+                expected: a.clone(),
+                found: Type::base("b"),
+            })
+        );
+        let church = Type::arrow(Type::arrow(a.clone(), a.clone()), Type::arrow(a.clone(), a));
+        assert_eq!(2_u32.to_church().check_type(&vec![], &church), Ok(()));
+    }
+
+    #[test]
+    fn hindley_milner_inference() {
+        use super::stlc::InferError;
+        let infer = |src: &str| src.parse::<Expr>().unwrap().infer().map(|s| s.to_string());
+        assert_eq!(infer("λx. x"), Ok("a → a".to_string()));
+        assert_eq!(
+            infer("λf g x. f (g x)"),
+            Ok("(a → b) → (c → a) → c → b".to_string())
+        );
+        assert_eq!(infer("λx y. x"), Ok("a → b → a".to_string()));
+        assert_eq!(infer("λx y. y"), Ok("a → b → b".to_string()));
+        // A list of both booleans forces them to one type.
+        assert_eq!(
+            infer("λc z. c (λx y. x) (c (λx y. y) z)"),
+            Ok("((a → a → a) → b → b) → b → b".to_string())
+        );
+        assert!(matches!(infer("λx. x x"), Err(InferError::Unify { .. })));
+    }
+
+    #[test]
+    fn compose_avoids_capture() {
+        let three = 3_u32.to_church();
+        let composed = terms::succ().compose(&terms::succ()).apply(&three);
+        assert_eq!(composed.reduce_to_numeral(100), Ok(5));
+        let twice = terms::succ().twice().twice().apply(&Expr::church_one());
+        assert_eq!(twice.reduce_to_numeral(100), Ok(5));
+        let add_x = terms::add().apply(&Expr::var("x"));
+        let shifted = add_x.compose(&terms::succ());
+        assert_eq!(shifted.fv(), ["x"]);
+        let closed = shifted.substitution(&"x".to_string(), &2_u32.to_church());
+        assert_eq!(closed.apply(&three).reduce_to_numeral(100), Ok(6));
+    }
+
+    #[test]
+    fn trace_of_succ_zero_chains() {
+        let trace = terms::succ()
+            .call(&[0_u32])
+            .trace_json(100, super::Strategy::Normal);
+        assert_eq!(trace.len(), 3);
+        assert!(trace.windows(2).all(|w| w[0].after == w[1].before));
+        assert_eq!(trace[0].before, terms::succ().call(&[0_u32]).to_string());
+        let last = trace.last().unwrap();
+        assert_eq!((last.after.as_str(), last.normal), ("λf x. f x", true));
+        assert!(trace[..2].iter().all(|entry| !entry.normal));
+    }
+
+    #[test]
+    fn checked_reduction_tells_cycles_from_growth() {
+        use super::Divergence;
+        let parse = |src: &str| src.parse::<Expr>().unwrap();
+        let omega = parse("(λx. x x) (λx. x x)");
+        assert_eq!(
+            omega.full_reduction_bounded_checked(50),
+            Err(Divergence::Cycle { step: 1 })
+        );
+        let omega3 = parse("(λx. x x x) (λx. x x x)");
+        assert_eq!(
+            omega3.full_reduction_bounded_checked(8),
+            Err(Divergence::OutOfSteps)
+        );
+        let one = terms::succ()
+            .call(&[0_u32])
+            .full_reduction_bounded_checked(50);
+        assert!(one.unwrap().alpha_eq(&Expr::church_one()));
+    }
+
+    #[test]
+    fn equivalence_accepts_open_terms() {
+        let parse = |src: &str| src.parse::<Expr>().unwrap();
+        assert_eq!(parse("(λx. x) y").equivalence(&parse("y")), Some(true));
+        assert_eq!(parse("(λx. x) y").equivalence(&parse("z")), Some(false));
+        assert_eq!(parse("λx. y x").equivalence(&parse("λz. y z")), Some(true));
+    }
+
+    #[test]
+    fn equivalent_within_accepts_open_terms() {
+        let parse = |src: &str| src.parse::<Expr>().unwrap();
+        let (redex, y) = (parse("(λx. x) y"), parse("y"));
+        assert_eq!(
+            redex.equivalent_within(&y, 10, super::Strategy::Normal),
+            Some(true)
+        );
+        assert_eq!(
+            redex.equivalent_within(&y, 10, super::Strategy::Applicative),
+            Some(true)
+        );
+        assert!(redex.equivalence_unbounded(&y));
+        assert!(!redex.equivalence_unbounded(&parse("z")));
+        assert!(parse("(λx. λy. x) y")
+            .full_reduction()
+            .alpha_eq(&parse("λz. y")));
+    }
+
+    #[test]
+    fn normalize_shared_reuses_subterms() {
+        let e = terms::mul().apply_all(&[terms::mul().call(&[2_u32, 2]), 3_u32.to_church()]);
+        let (normal, cache) = e.normalize_shared_counted();
+        assert_eq!(normal.to_numeral(), Ok(12));
+        assert!(cache.shared > 0);
+        // Each normal-order step rebuilds the whole term, so the named
+        // reducer allocates at least the sizes of the terms it passes through.
+        let copied: usize = e
+            .reduction_steps(super::Strategy::Normal)
+            .map(|step| step.size())
+            .sum();
+        assert!(
+            cache.built * 10 < copied,
+            "{} nodes against {copied}",
+            cache.built
+        );
+    }
+
+    #[test]
+    fn nbe_finishes_past_the_step_budget() {
+        // 321 normal-order steps for 6!, and 2048 for 2^10.
+        let factorial: ChurchExpr = (1..=6).map(Church).product();
+        let exp = terms::exp().call(&[2_u32, 10]);
+        for (e, budget, value) in [(factorial.0, 100, 720), (exp, 1_000, 1024)] {
+            let cfg = EvalConfig::default().max_steps(budget);
+            assert_eq!(e.normalize_with(&cfg), Err(EvalError::Diverged));
+            assert_eq!(e.normalize_nbe().to_numeral(), Ok(value));
+        }
+    }
+
+    #[test]
+    fn full_reduction_bounded_returns_only_normal_forms() {
+        let omega = "(λx. x x) (λx. x x)".parse::<Expr>().unwrap();
+        assert_eq!(omega.full_reduction_bounded(50), None);
+        let one = terms::succ().call(&[0_u32]).full_reduction_bounded(50);
+        assert!(one.is_some_and(|e| e.alpha_eq(&Expr::church_one())));
+        assert_eq!(terms::t().full_reduction_bounded(0), None);
+        assert_eq!(terms::t().full_reduction_bounded(1), Some(terms::t()));
+    }
+
+    #[test]
+    fn strategies_agree_on_the_builtins() {
+        let samples = [
+            Expr::church_zero(),
+            2_u32.to_church(),
+            terms::t(),
+            terms::f(),
+        ];
+        let mut corpus = vec![];
+        // The fixed-point combinators, and `div` and `mod` which recurse
+        // through `Z`, unfold forever under a binder. They would only spend
+        // the budget.
+        let names: Vec<&str> = terms::builtin_names()
+            .into_iter()
+            .filter(|name| !["Y", "Z", "div", "mod"].contains(name))
+            .collect();
+        for &name in &names {
+            let head = terms::builtin(name).unwrap();
+            corpus.push(head.clone());
+            for a in &samples {
+                corpus.push(head.apply(a));
+                for b in &samples {
+                    corpus.push(head.apply(a).apply(b));
+                }
+            }
+        }
+        assert_eq!(corpus.len(), names.len() * 21);
+        assert!(super::strategies_agree_on(&corpus, 500));
+        // Every term finishes under every strategy, so none of the
+        // comparisons above was skipped.
+        let finished = corpus
+            .iter()
+            .filter(|e| {
+                super::Strategy::ALL
+                    .iter()
+                    .all(|&s| e.reduce_with(s, 500).is_some())
+            })
+            .count();
+        assert_eq!(finished, corpus.len());
+    }
+
+    #[test]
+    fn assert_reduces_to_numerals_booleans_and_terms() {
+        assert_reduces_to!(terms::add().call(&[2_u32, 3]), 5_u32);
+        assert_reduces_to!(
+            terms::mul().call(&[3_u32, 4]),
+            terms::add().call(&[6_u32, 6])
+        );
+        assert_reduces_to!(terms::and().call(&[true, false]), false);
+        assert_reduces_to!(terms::leq().call(&[2_u32, 3]), true);
+        let skk =
+            terms::combinators::s().apply_all(&[terms::combinators::k(), terms::combinators::k()]);
+        assert_reduces_to!(skk, "λy. y".parse::<Expr>().unwrap());
+        // Open terms compare their free variables by name.
+        assert_reduces_to!(
+            terms::t().apply(&Expr::var("a")),
+            Expr::lambda("b", Expr::var("a"))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "first difference")]
+    fn assert_reduces_to_reports_the_first_difference() {
+        assert_reduces_to!(terms::succ().call(&[2_u32]), 4_u32);
+    }
+
+    #[test]
+    #[should_panic(expected = "has no normal form within 10000 steps")]
+    fn assert_reduces_to_fails_on_divergence() {
+        let omega = "(λx. x x) (λx. x x)".parse::<Expr>().unwrap();
+        assert_reduces_to!(omega, 0_u32);
+    }
+
+    #[test]
+    fn church_five_decodes_to_five() {
+        assert_eq!(5_u32.to_church().to_numeral(), Ok(5));
+    }
+
+    // The JSON the browser entry points return, checked from Rust.
+    #[cfg(feature = "wasm")]
+    mod wasm {
+        use super::*;
+        use crate::wasm::normalize_json;
+        use serde_json::Value;
+
+        fn json(text: &str) -> Value {
+            serde_json::from_str(text).unwrap()
+        }
+
+        #[test]
+        fn normalize_json_reports_the_normal_form() {
+            let out = json(&normalize_json("(λx. x) y", 100));
+            assert_eq!(out["text"], "y");
+            let term: Expr = serde_json::from_value(out["term"].clone()).unwrap();
+            assert_eq!(term, Expr::var("y"));
+            assert!(out.get("error").is_none());
+        }
+
+        #[test]
+        fn normalize_json_reports_errors() {
+            let parse_error = json(&normalize_json("λx y x", 100));
+            assert_eq!(parse_error["error"]["message"], "expected '.' after binder");
+            assert_eq!(parse_error["error"]["offset"], 7);
+            let out_of_steps = json(&normalize_json("(λx. x x) (λx. x x)", 100));
+            assert_eq!(
+                out_of_steps["error"]["message"],
+                "no normal form within 100 steps"
+            );
+            assert!(out_of_steps["error"].get("offset").is_none());
+        }
+    }
+
+    // Every normalizer on the same closed terms. Church-Rosser says the ones
+    // that finish agree; here that is checked on de Bruijn forms, so renamed
+    // binders do not matter.
+    mod differential {
+        use super::*;
+
+        const BUDGET: usize = 300;
+        // A parallel pass contracts every redex it meets, so a diverging term
+        // can grow by a power with each one.
+        const PASSES: usize = 40;
+
+        fn debruijn_within(e: &Expr, max_steps: usize) -> Option<DeBrujin> {
+            let mut term = e.debrujin();
+            for _ in 0..max_steps {
+                match term.reduce() {
+                    Some(next) => term = next,
+                    None => return Some(term),
+                }
+            }
+            term.reduce().is_none().then_some(term)
+        }
+
+        // The reducers that stop by themselves run under `BUDGET`. The others
+        // only run where a bounded one with the same evaluation order
+        // finished: the lazy and shared ones after normal order, NbE, which
+        // evaluates arguments first, after applicative order.
+        fn agree(e: &Expr) -> Result<(), String> {
+            use super::super::Strategy::*;
+            let normal = e.reduce_with(Normal, BUDGET);
+            let applicative = e.reduce_with(Applicative, BUDGET);
+            let indices = debruijn_within(e, BUDGET);
+            // Both contract the leftmost-outermost redex, so they take the
+            // same number of steps, and arithmetic only saves steps.
+            if normal.is_some() != indices.is_some() {
+                return Err(format!(
+                    "{e}: named and de Bruijn normal order disagree on termination"
+                ));
+            }
+            let numeral_aware = e.reduce_with(NumeralAware, BUDGET);
+            if normal.is_some() && numeral_aware.is_none() {
+                return Err(format!(
+                    "{e}: normal order finished but numeral-aware did not"
+                ));
+            }
+            let mut results = vec![
+                ("applicative", applicative.clone()),
+                ("numeral-aware", numeral_aware),
+                ("parallel passes", e.full_reduction_bounded(PASSES)),
+            ];
+            if normal.is_some() {
+                results.push(("shared", Some(e.normalize_shared())));
+                results.push(("lazy", Some(e.normalize_lazy())));
+            }
+            if applicative.is_some() {
+                results.push(("nbe", Some(e.normalize_nbe())));
+            }
+            let expected = indices.or_else(|| {
+                results
+                    .iter()
+                    .find_map(|(_, r)| r.as_ref())
+                    .map(Expr::debrujin)
+            });
+            for (name, result) in results {
+                if let (Some(result), Some(expected)) = (result, &expected) {
+                    if result.debrujin() != *expected {
+                        return Err(format!("{e}: {name} gave {result}, expected {expected}"));
+                    }
+                }
+            }
+            if let (Some(normal), Some(expected)) = (normal, &expected) {
+                if normal.debrujin() != *expected {
+                    return Err(format!("{e}: normal order gave {normal}"));
+                }
+            }
+            Ok(())
+        }
+
+        fn samples() -> Vec<Expr> {
+            let mut terms: Vec<Expr> = terms::builtin_names()
+                .into_iter()
+                .filter_map(terms::builtin)
+                .collect();
+            terms.extend([
+                terms::add().call(&[2_u32, 3]),
+                terms::mul().call(&[2_u32, 3]),
+                terms::exp().call(&[2_u32, 3]),
+                terms::pred().call(&[3_u32]),
+                terms::sub().call(&[5_u32, 2]),
+                terms::eq_nat().call(&[2_u32, 2]),
+                terms::div().call(&[6_u32, 2]),
+                terms::applied(
+                    terms::first(),
+                    [terms::tuple(&1.to_lambda(), &2.to_lambda())],
+                ),
+                terms::length().apply(&terms::applied(
+                    terms::cons(),
+                    [1.to_lambda(), terms::nil()],
+                )),
+            ]);
+            terms
+        }
+
+        #[test]
+        fn reducers_agree_on_the_library() {
+            for e in samples() {
+                agree(&e).unwrap();
+            }
+        }
+
+        #[test]
+        fn bounded_reducers_all_give_up_on_divergent_terms() {
+            let omega = "(λx. x x) (λx. x x)".parse::<Expr>().unwrap();
+            let y_id = terms::combinators::y().apply(&terms::combinators::i());
+            for e in [omega, y_id, terms::combinators::z()] {
+                for strategy in super::super::Strategy::ALL {
+                    assert_eq!(
+                        e.reduce_with(strategy, BUDGET),
+                        None,
+                        "{e} under {strategy:?}"
+                    );
+                }
+                assert!(e
+                    .full_reduction_bounded(PASSES)
+                    .is_none_or(|stuck| !stuck.is_normal()));
+                assert_eq!(debruijn_within(&e, BUDGET), None);
+                agree(&e).unwrap();
+            }
+        }
+
+        proptest! {
+            #[test]
+            fn reducers_agree_on_closed_terms(e in arb_closed()) {
+                agree(&e).map_err(TestCaseError::fail)?;
+            }
+        }
+    }
+}
+
+// Run with `cargo test --no-default-features --features alloc`.
+#[cfg(all(test, not(feature = "std")))]
+mod no_std {
+    use super::terms::{self, ChurchNumeral};
+    use super::Expr;
+    use alloc::string::ToString;
+
+    #[test]
+    fn terms_build_parse_and_print_with_alloc_only() {
+        let two: Expr = "λf x. f (f x)".parse().unwrap();
+        assert_eq!(two, 2_u32.to_church());
+        assert_eq!(two.to_string(), "λf x. f (f x)");
+        assert_eq!(two.debrujin().to_string(), "λ. λ. 1 (1 0)");
+        assert!(two.debrujin().to_named().alpha_eq(&two));
+        let five = terms::add().call(&[2_u32, 3]).full_reduction();
+        assert_eq!(five.to_numeral(), Ok(5));
+    }
+}
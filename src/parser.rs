@@ -0,0 +1,359 @@
+use super::*;
+use terms::ChurchNumeral;
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParseError {
+    pub message: String,
+    // Byte offset into the input.
+    pub offset: usize,
+    // The line of the input the offset is on, and how many characters of it
+    // come before the offset.
+    pub snippet: String,
+    pub column: usize,
+}
+
+// The message, then the line with a caret under the offending character:
+//
+//     expected '.' after binder
+//     λx x
+//         ^
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", self.message)?;
+        writeln!(f, "{}", self.snippet)?;
+        write!(f, "{:>width$}", "^", width = self.column + 1)
+    }
+}
+
+impl core::str::FromStr for Expr {
+    type Err = ParseError;
+
+    // Accepts `λx. e` (or `\x. e`), `λx y. e` for nested binders,
+    // left-associative application, parentheses and `let x = e1 in e2` for
+    // `(λx. e2) e1`. A lambda or `let` body extends as far to the right as
+    // possible.
+    //
+    // Literals are sugar: a decimal number `n` is the Church numeral
+    // `n.to_church()`, and `true` and `false` are `terms::t()` and
+    // `terms::f()`. So `add 5 7` is `add (λf x. f (f ...)) (λf x. ...)`, with
+    // `add` still a free variable, and `true` cannot be a binder name.
+    fn from_str(src: &str) -> Result<Expr, ParseError> {
+        Parser::new(src).parse()
+    }
+}
+
+// Top-level definitions followed by an optional term, e.g.
+//
+//     let two = λf x. f (f x)
+//     let four = two two
+//     four
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Program {
+    pub defs: Vec<(Id, Expr)>,
+    pub body: Option<Expr>,
+}
+
+impl Program {
+    // The body with every definition bound around it by `let`.
+    pub fn to_expr(&self) -> Option<Expr> {
+        let body = self.body.clone()?;
+        Some(self.defs.iter().rev().fold(body, |acc, (id, value)| {
+            Expr::Lam(id.clone(), Box::new(acc)).apply(value)
+        }))
+    }
+
+    // Every definition with the earlier ones substituted in, so each entry
+    // stands on its own.
+    #[cfg(feature = "std")]
+    pub fn env(&self) -> Env {
+        let mut env = Env::new();
+        let mut resolved: Vec<(Id, Expr)> = vec![];
+        for (id, value) in &self.defs {
+            let value = resolved
+                .iter()
+                .rev()
+                .fold(value.clone(), |acc, (prev, body)| {
+                    acc.substitution(prev, body)
+                });
+            env.insert(id.clone(), value.clone());
+            resolved.push((id.clone(), value));
+        }
+        env
+    }
+}
+
+impl core::str::FromStr for Program {
+    type Err = ParseError;
+
+    fn from_str(src: &str) -> Result<Program, ParseError> {
+        Parser::new(src).parse_program()
+    }
+}
+
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (id, value) in &self.defs {
+            writeln!(f, "let {} = {}", id, value)?;
+        }
+        if let Some(body) = &self.body {
+            write!(f, "{}", body)?;
+        }
+        Ok(())
+    }
+}
+
+const KEYWORDS: [&str; 4] = ["let", "in", "true", "false"];
+
+pub struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+    // When set, a newline outside parentheses ends the current term.
+    line_mode: bool,
+    parens: usize,
+    // When set, a name may start with `?`, for `Pattern`.
+    metavariables: bool,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(src: &'a str) -> Parser<'a> {
+        Parser {
+            src,
+            pos: 0,
+            line_mode: false,
+            parens: 0,
+            metavariables: false,
+        }
+    }
+
+    pub(crate) fn with_metavariables(mut self) -> Parser<'a> {
+        self.metavariables = true;
+        self
+    }
+
+    pub fn parse(mut self) -> Result<Expr, ParseError> {
+        let expr = self.expr()?;
+        self.end()?;
+        Ok(expr)
+    }
+
+    // A sequence of `let name = term` definitions, optionally followed by
+    // a term. Each definition ends at a newline (outside parentheses) or
+    // `;`. A definition followed by `in` is read as part of the term.
+    pub fn parse_program(mut self) -> Result<Program, ParseError> {
+        let mut defs = vec![];
+        loop {
+            let start = self.pos;
+            if !self.keyword("let") {
+                break;
+            }
+            self.line_mode = true;
+            let (name, value) = self.definition()?;
+            let is_let_in = self.keyword("in");
+            self.line_mode = false;
+            if is_let_in {
+                self.pos = start;
+                break;
+            }
+            defs.push((name, value));
+            self.skip_whitespace();
+            if self.peek() == Some(';') {
+                self.bump();
+            }
+        }
+        self.skip_whitespace();
+        let body = match self.peek() {
+            None => None,
+            Some(_) => Some(self.expr()?),
+        };
+        self.end()?;
+        Ok(Program { defs, body })
+    }
+
+    fn end(&mut self) -> Result<(), ParseError> {
+        self.skip_whitespace();
+        match self.peek() {
+            None => Ok(()),
+            Some(c) => Err(self.error(format!("unexpected '{c}'"))),
+        }
+    }
+
+    // `name = term`, after the `let`.
+    fn definition(&mut self) -> Result<(Id, Expr), ParseError> {
+        let name = match self.ident() {
+            Some(name) => name,
+            None => return Err(self.error("expected a name after 'let'".to_string())),
+        };
+        self.expect('=', "expected '=' after name")?;
+        Ok((name, self.expr()?))
+    }
+
+    // `let x = e1 in e2`, read as `(λx. e2) e1`.
+    fn let_in(&mut self) -> Result<Expr, ParseError> {
+        let (name, value) = self.definition()?;
+        if !self.keyword("in") {
+            return Err(self.error("expected 'in'".to_string()));
+        }
+        let body = self.expr()?;
+        Ok(Expr::Lam(name, Box::new(body)).apply(&value))
+    }
+
+    fn error(&self, message: String) -> ParseError {
+        let start = self.src[..self.pos].rfind('\n').map_or(0, |i| i + 1);
+        let end = self.src[self.pos..]
+            .find('\n')
+            .map_or(self.src.len(), |i| self.pos + i);
+        ParseError {
+            message,
+            offset: self.pos,
+            snippet: self.src[start..end].to_string(),
+            column: self.src[start..self.pos].chars().count(),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) {
+        if let Some(c) = self.peek() {
+            self.pos += c.len_utf8();
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if !c.is_whitespace() || (c == '\n' && self.line_mode && self.parens == 0) {
+                break;
+            }
+            self.bump();
+        }
+    }
+
+    fn expect(&mut self, expected: char, message: &str) -> Result<(), ParseError> {
+        self.skip_whitespace();
+        if self.peek() == Some(expected) {
+            self.bump();
+            Ok(())
+        } else {
+            Err(self.error(message.to_string()))
+        }
+    }
+
+    fn word(&mut self) -> Option<&'a str> {
+        self.skip_whitespace();
+        let start = self.pos;
+        if self.metavariables && self.peek() == Some('?') {
+            self.bump();
+        }
+        if !self.peek().is_some_and(|c| c.is_alphabetic() || c == '_') {
+            self.pos = start;
+            return None;
+        }
+        while self
+            .peek()
+            .is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '\'')
+        {
+            self.bump();
+        }
+        Some(&self.src[start..self.pos])
+    }
+
+    fn ident(&mut self) -> Option<Id> {
+        let start = self.pos;
+        match self.word() {
+            Some(w) if !KEYWORDS.contains(&w) => Some(w.to_string()),
+            _ => {
+                self.pos = start;
+                None
+            }
+        }
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let start = self.pos;
+        if self.word() == Some(keyword) {
+            true
+        } else {
+            self.pos = start;
+            false
+        }
+    }
+
+    fn expr(&mut self) -> Result<Expr, ParseError> {
+        self.skip_whitespace();
+        if let Some('λ' | '\\') = self.peek() {
+            return self.lambda();
+        }
+        if self.keyword("let") {
+            return self.let_in();
+        }
+        let mut expr = match self.atom()? {
+            Some(atom) => atom,
+            None => return Err(self.error("expected a term".to_string())),
+        };
+        loop {
+            self.skip_whitespace();
+            if let Some('λ' | '\\') = self.peek() {
+                return Ok(expr.apply(&self.lambda()?));
+            }
+            match self.atom()? {
+                Some(atom) => expr = expr.apply(&atom),
+                None => return Ok(expr),
+            }
+        }
+    }
+
+    fn lambda(&mut self) -> Result<Expr, ParseError> {
+        self.bump();
+        let mut binders = vec![];
+        while let Some(id) = self.ident() {
+            binders.push(id);
+        }
+        if binders.is_empty() {
+            return Err(self.error("expected a binder after 'λ'".to_string()));
+        }
+        self.expect('.', "expected '.' after binder")?;
+        let body = self.expr()?;
+        Ok(binders
+            .into_iter()
+            .rev()
+            .fold(body, |acc, id| Expr::Lam(id, Box::new(acc))))
+    }
+
+    fn atom(&mut self) -> Result<Option<Expr>, ParseError> {
+        self.skip_whitespace();
+        if self.peek() == Some('(') {
+            self.bump();
+            self.parens += 1;
+            let expr = self.expr()?;
+            self.expect(')', "expected ')'")?;
+            self.parens -= 1;
+            return Ok(Some(expr));
+        }
+        if self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            return self.numeral().map(Some);
+        }
+        if self.keyword("true") {
+            return Ok(Some(terms::t()));
+        }
+        if self.keyword("false") {
+            return Ok(Some(terms::f()));
+        }
+        Ok(self.ident().map(Expr::Var))
+    }
+
+    fn numeral(&mut self) -> Result<Expr, ParseError> {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+            self.bump();
+        }
+        match self.src[start..self.pos].parse::<u32>() {
+            Ok(n) => Ok(n.to_church()),
+            Err(_) => {
+                self.pos = start;
+                Err(self.error("numeral does not fit in a u32".to_string()))
+            }
+        }
+    }
+}
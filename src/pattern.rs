@@ -0,0 +1,175 @@
+// Patterns for user-defined rules: terms with metavariables, written `?m`,
+// that stand for any subterm. A metavariable in binder position, as in
+// `λ?x. ?f ?x`, stands for whatever name the term binds there, and its uses
+// in the body match only that variable. Matching is first-order: nothing is
+// reduced, and the rest of the pattern matches up to alpha.
+
+use super::*;
+use alloc::collections::BTreeMap;
+use parser::Parser;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Shape {
+    Lam(Binder, Box<Shape>),
+    App(Box<Shape>, Box<Shape>),
+    Var(Id),
+    Meta(Id),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Binder {
+    Name(Id),
+    Meta(Id),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pattern {
+    shape: Shape,
+    // `(x, m)`: the variable bound to `?x` must not occur free in what `?m`
+    // stands for.
+    not_free: Vec<(Id, Id)>,
+}
+
+impl Pattern {
+    // Reads every variable and binder of `e` whose name starts with `?` as
+    // a metavariable.
+    pub fn from_expr(e: &Expr) -> Pattern {
+        Pattern {
+            shape: shape(e),
+            not_free: vec![],
+        }
+    }
+
+    // Adds the side condition that the variable bound to the binder
+    // metavariable `?x` is not free in `?m`, as eta needs:
+    // `"λ?x. ?f ?x".parse::<Pattern>()?.not_free_in("x", "f")`.
+    pub fn not_free_in(mut self, x: &str, m: &str) -> Pattern {
+        self.not_free.push((x.to_string(), m.to_string()));
+        self
+    }
+}
+
+fn shape(e: &Expr) -> Shape {
+    match e {
+        Expr::Lam(id, body) => {
+            let binder = match id.strip_prefix('?') {
+                Some(meta) => Binder::Meta(meta.to_string()),
+                None => Binder::Name(id.clone()),
+            };
+            Shape::Lam(binder, Box::new(shape(body)))
+        }
+        Expr::App(m, n) => Shape::App(Box::new(shape(m)), Box::new(shape(n))),
+        Expr::Var(id) => match id.strip_prefix('?') {
+            Some(meta) => Shape::Meta(meta.to_string()),
+            None => Shape::Var(id.clone()),
+        },
+    }
+}
+
+impl core::str::FromStr for Pattern {
+    type Err = ParseError;
+
+    // The term syntax, with `?name` allowed wherever a name is.
+    fn from_str(src: &str) -> Result<Pattern, ParseError> {
+        let e = Parser::new(src).with_metavariables().parse()?;
+        Ok(Pattern::from_expr(&e))
+    }
+}
+
+// What each metavariable of a matched pattern stands for, keyed by its name
+// without the `?`. A binder metavariable stands for the variable it bound.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Bindings {
+    terms: BTreeMap<Id, Expr>,
+}
+
+impl Bindings {
+    pub fn get(&self, meta: &str) -> Option<&Expr> {
+        self.terms.get(meta)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Id, &Expr)> {
+        self.terms.iter()
+    }
+}
+
+// A binder passed on the way down: the pattern's and the term's.
+type Scope<'a> = Vec<(&'a Binder, &'a Id)>;
+
+impl Expr {
+    // Matches the whole of `self` against `pat`. A metavariable used twice
+    // must stand for alpha-equivalent subterms both times.
+    pub fn match_pattern(&self, pat: &Pattern) -> Option<Bindings> {
+        let mut bindings = Bindings::default();
+        if !self.match_shape(&pat.shape, &mut vec![], &mut bindings) {
+            return None;
+        }
+        for (x, m) in &pat.not_free {
+            if let (Some(Expr::Var(v)), Some(term)) = (bindings.get(x), bindings.get(m)) {
+                if term.occurs_free(v) {
+                    return None;
+                }
+            }
+        }
+        Some(bindings)
+    }
+
+    fn match_shape<'a>(
+        &'a self,
+        pat: &'a Shape,
+        scope: &mut Scope<'a>,
+        bindings: &mut Bindings,
+    ) -> bool {
+        // How far out the binder of a name is, on either side.
+        let term_binder = |scope: &Scope, y: &Id| scope.iter().rev().position(|(_, t)| *t == y);
+        match (pat, self) {
+            (Shape::Lam(binder, body), Expr::Lam(y, m)) => {
+                if let Binder::Meta(x) = binder {
+                    if !bind(bindings, x, &Expr::Var(y.clone())) {
+                        return false;
+                    }
+                }
+                scope.push((binder, y));
+                let matched = m.match_shape(body, scope, bindings);
+                scope.pop();
+                matched
+            }
+            (Shape::App(p, q), Expr::App(m, n)) => {
+                m.match_shape(p, scope, bindings) && n.match_shape(q, scope, bindings)
+            }
+            (Shape::Var(x), Expr::Var(y)) => {
+                let pattern_binder = scope
+                    .iter()
+                    .rev()
+                    .position(|(b, _)| **b == Binder::Name(x.clone()));
+                match (pattern_binder, term_binder(scope, y)) {
+                    (None, None) => x == y,
+                    (i, j) => i == j,
+                }
+            }
+            (Shape::Meta(m), _) => {
+                let in_scope = scope
+                    .iter()
+                    .rev()
+                    .position(|(b, _)| **b == Binder::Meta(m.clone()));
+                match (in_scope, self) {
+                    (Some(i), Expr::Var(y)) => term_binder(scope, y) == Some(i),
+                    (Some(_), _) => false,
+                    (None, _) => bind(bindings, m, self),
+                }
+            }
+            _ => false,
+        }
+    }
+}
+
+// Records `m := e`, or checks `e` against what `m` already stands for.
+fn bind(bindings: &mut Bindings, m: &Id, e: &Expr) -> bool {
+    match bindings.terms.get(m) {
+        Some(bound) => bound.alpha_eq(e),
+        None => {
+            bindings.terms.insert(m.clone(), e.clone());
+            true
+        }
+    }
+}
@@ -0,0 +1,353 @@
+use super::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Notation {
+    Named,
+    DeBruijn,
+    Both,
+}
+
+// Printer options, combined with `|`. `ASCII` writes `\x. x` instead of
+// `λx. x`; `COLLAPSE` merges nested binders into `λf x. f x`. `LATEX`
+// writes math-mode source such as `\lambda f\, x.\, f\; x`, and with
+// `SUBSCRIPTS` a bound variable ending in digits, like `x12`, prints as
+// `x_{12}`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PrintStyle(u8);
+
+impl PrintStyle {
+    pub const ASCII: PrintStyle = PrintStyle(1);
+    pub const UNICODE: PrintStyle = PrintStyle(2);
+    pub const COLLAPSE: PrintStyle = PrintStyle(4);
+    pub const LATEX: PrintStyle = PrintStyle(8);
+    pub const SUBSCRIPTS: PrintStyle = PrintStyle(16);
+
+    pub fn contains(self, other: PrintStyle) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl core::ops::BitOr for PrintStyle {
+    type Output = PrintStyle;
+
+    fn bitor(self, other: PrintStyle) -> PrintStyle {
+        PrintStyle(self.0 | other.0)
+    }
+}
+
+// What `Display` uses.
+impl Default for PrintStyle {
+    fn default() -> PrintStyle {
+        PrintStyle::UNICODE | PrintStyle::COLLAPSE
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_string_styled(PrintStyle::default()))
+    }
+}
+
+// A byte range into the rendered term.
+pub type Span = core::ops::Range<usize>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TokenKind {
+    Lambda,
+    // Binders are numbered from 0 in the order they are printed.
+    Binder(u32),
+    Bound(u32),
+    Free,
+    Dot,
+    Paren,
+}
+
+impl Expr {
+    // Graphviz source for the syntax tree. Node ids come from a pre-order
+    // counter, so the output only depends on the term.
+    pub fn to_dot(&self) -> String {
+        self.dot(false)
+    }
+
+    // Like `to_dot`, with an extra dashed edge from each bound occurrence to
+    // its binder.
+    pub fn to_dot_linked(&self) -> String {
+        self.dot(true)
+    }
+
+    fn dot(&self, link_binders: bool) -> String {
+        let mut out = String::from("digraph {\n");
+        let mut counter = 0;
+        self.dot_with(&mut counter, &mut vec![], link_binders, &mut out);
+        out.push_str("}\n");
+        out
+    }
+
+    fn dot_with(
+        &self,
+        counter: &mut usize,
+        scope: &mut Vec<(Id, usize)>,
+        link_binders: bool,
+        out: &mut String,
+    ) -> usize {
+        let node = *counter;
+        *counter += 1;
+        match self {
+            Expr::Lam(id, body) => {
+                out.push_str(&format!("  n{node} [label=\"λ{id}\"];\n"));
+                scope.push((id.clone(), node));
+                let child = body.dot_with(counter, scope, link_binders, out);
+                scope.pop();
+                out.push_str(&format!("  n{node} -> n{child};\n"));
+            }
+            Expr::App(m, n) => {
+                out.push_str(&format!("  n{node} [label=\"@\"];\n"));
+                let left = m.dot_with(counter, scope, link_binders, out);
+                let right = n.dot_with(counter, scope, link_binders, out);
+                out.push_str(&format!("  n{node} -> n{left};\n"));
+                out.push_str(&format!("  n{node} -> n{right};\n"));
+            }
+            Expr::Var(id) => {
+                out.push_str(&format!("  n{node} [label=\"{id}\"];\n"));
+                if link_binders {
+                    if let Some((_, binder)) = scope.iter().rev().find(|(x, _)| x == id) {
+                        out.push_str(&format!("  n{node} -> n{binder} [style=dashed];\n"));
+                    }
+                }
+            }
+        }
+        node
+    }
+
+    pub fn to_string_styled(&self, style: PrintStyle) -> String {
+        let mut printer = printer::Printer::new(style);
+        printer.print(self, true);
+        printer.out
+    }
+
+    // The `Display` rendering as LaTeX math, parenthesized the same way.
+    // Multi-letter names are set in `\mathit`.
+    pub fn to_latex(&self) -> String {
+        self.to_string_styled(PrintStyle::LATEX | PrintStyle::COLLAPSE)
+    }
+
+    // `to_latex` with bound variables' trailing digits as subscripts, which
+    // suits `to_canonical` output: `\lambda x_{0}.\, x_{0}`.
+    pub fn to_latex_subscripted(&self) -> String {
+        self.to_string_styled(PrintStyle::LATEX | PrintStyle::COLLAPSE | PrintStyle::SUBSCRIPTS)
+    }
+
+    // The `Display` rendering, cut into tokens for highlighting. Binders and
+    // the occurrences they bind carry the same number.
+    pub fn to_annotated(&self) -> Vec<(Span, TokenKind)> {
+        let mut printer = printer::Printer::new(PrintStyle::default());
+        printer.print(self, true);
+        printer.tokens
+    }
+
+    pub fn render(&self, n: Notation) -> String {
+        match n {
+            Notation::Named => self.to_string(),
+            Notation::DeBruijn => self.debrujin().to_string(),
+            Notation::Both => format!("{}\n{}", self, self.debrujin()),
+        }
+    }
+}
+
+// What the printer needs from a term representation, one node at a time.
+// Both `Expr` and `DeBrujin` go through the same printer, so they agree on
+// where parentheses go.
+pub trait Nameable {
+    fn node(&self) -> Node<'_, Self>;
+}
+
+pub enum Node<'a, T: ?Sized> {
+    // A binder without a name prints as `λ.`.
+    Lam(Option<&'a Id>, &'a T),
+    App(&'a T, &'a T),
+    Var(&'a Id),
+    // A de Bruijn index, counting binders outwards from 0.
+    Index(u32),
+}
+
+impl Nameable for Expr {
+    fn node(&self) -> Node<'_, Expr> {
+        match self {
+            Expr::Lam(id, body) => Node::Lam(Some(id), body),
+            Expr::App(m, n) => Node::App(m, n),
+            Expr::Var(id) => Node::Var(id),
+        }
+    }
+}
+
+impl Nameable for DeBrujin {
+    fn node(&self) -> Node<'_, DeBrujin> {
+        match self {
+            DeBrujin::Lam(body) => Node::Lam(None, body),
+            DeBrujin::App(m, n) => Node::App(m, n),
+            DeBrujin::Var(index) => Node::Index(*index),
+        }
+    }
+}
+
+impl fmt::Display for DeBrujin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut printer = printer::Printer::new(PrintStyle::default());
+        printer.print(self, true);
+        f.write_str(&printer.out)
+    }
+}
+
+mod printer {
+    use super::*;
+
+    pub struct Printer<'a> {
+        style: PrintStyle,
+        pub out: String,
+        pub tokens: Vec<(Span, TokenKind)>,
+        // Enclosing binders with their numbers, innermost last.
+        scope: Vec<(Option<&'a Id>, u32)>,
+        binders: u32,
+    }
+
+    impl<'a> Printer<'a> {
+        pub fn new(style: PrintStyle) -> Printer<'a> {
+            Printer {
+                style,
+                out: String::new(),
+                tokens: vec![],
+                scope: vec![],
+                binders: 0,
+            }
+        }
+
+        fn token(&mut self, text: &str, kind: TokenKind) {
+            let start = self.out.len();
+            self.out.push_str(text);
+            self.tokens.push((start..self.out.len(), kind));
+        }
+
+        fn latex(&self) -> bool {
+            self.style.contains(PrintStyle::LATEX)
+        }
+
+        // A space the reader should see: LaTeX drops plain ones in math.
+        fn space(&mut self, latex: &str) {
+            if self.latex() {
+                self.out.push_str(latex);
+            }
+            self.out.push(' ');
+        }
+
+        fn name(&self, id: &str, bound: bool) -> String {
+            if !self.latex() {
+                return id.to_string();
+            }
+            let primes = id.len() - id.trim_end_matches('\'').len();
+            let (base, primes) = id.split_at(id.len() - primes);
+            let mut digits = "";
+            let mut base = base;
+            if bound && self.style.contains(PrintStyle::SUBSCRIPTS) {
+                let stem = base.trim_end_matches(|c: char| c.is_ascii_digit());
+                if !stem.is_empty() {
+                    digits = &base[stem.len()..];
+                    base = stem;
+                }
+            }
+            let base = base.replace('_', "\\_");
+            let mut out = if base.chars().count() > 1 {
+                format!("\\mathit{{{base}}}")
+            } else {
+                base
+            };
+            if !digits.is_empty() {
+                out.push_str(&format!("_{{{digits}}}"));
+            }
+            out.push_str(primes);
+            out
+        }
+
+        fn binder(&mut self, id: Option<&'a Id>) {
+            let n = self.binders;
+            self.binders += 1;
+            if let Some(id) = id {
+                let text = self.name(id, true);
+                self.token(&text, TokenKind::Binder(n));
+            }
+            self.scope.push((id, n));
+        }
+
+        fn occurrence(&mut self, text: &str, binder: Option<u32>) {
+            let kind = match binder {
+                Some(n) => TokenKind::Bound(n),
+                None => TokenKind::Free,
+            };
+            let text = self.name(text, binder.is_some());
+            self.token(&text, kind);
+        }
+
+        // Prints with as few parentheses as the parser needs: application is
+        // left associative and a lambda extends as far right as possible, so
+        // it is only left bare in `tail` position, where nothing follows it.
+        // Nameless binders are never collapsed, since `λ λ.` would hide how
+        // many there are.
+        pub fn print<T: Nameable + ?Sized>(&mut self, e: &'a T, tail: bool) {
+            match e.node() {
+                Node::Lam(..) if !tail => self.parenthesized(e),
+                Node::Lam(id, body) => {
+                    let depth = self.scope.len();
+                    let lambda = if self.latex() {
+                        "\\lambda"
+                    } else if self.style.contains(PrintStyle::ASCII) {
+                        "\\"
+                    } else {
+                        "λ"
+                    };
+                    self.token(lambda, TokenKind::Lambda);
+                    if self.latex() && id.is_some() {
+                        self.out.push(' ');
+                    }
+                    self.binder(id);
+                    let mut body = body;
+                    while self.style.contains(PrintStyle::COLLAPSE) && id.is_some() {
+                        let Node::Lam(Some(id), inner) = body.node() else {
+                            break;
+                        };
+                        self.space("\\,");
+                        self.binder(Some(id));
+                        body = inner;
+                    }
+                    self.token(".", TokenKind::Dot);
+                    self.space("\\,");
+                    self.print(body, true);
+                    self.scope.truncate(depth);
+                }
+                Node::App(m, n) => {
+                    self.print(m, false);
+                    self.space("\\;");
+                    if let Node::App(..) = n.node() {
+                        self.parenthesized(n);
+                    } else {
+                        self.print(n, tail);
+                    }
+                }
+                Node::Var(id) => {
+                    let binder = self.scope.iter().rev().find(|(x, _)| *x == Some(id));
+                    self.occurrence(id, binder.map(|(_, n)| *n));
+                }
+                Node::Index(index) => {
+                    let binder = (self.scope.len() as u32)
+                        .checked_sub(index + 1)
+                        .map(|at| self.scope[at as usize].1);
+                    self.occurrence(&index.to_string(), binder);
+                }
+            }
+        }
+
+        fn parenthesized<T: Nameable + ?Sized>(&mut self, e: &'a T) {
+            self.token("(", TokenKind::Paren);
+            self.print(e, true);
+            self.token(")", TokenKind::Paren);
+        }
+    }
+}
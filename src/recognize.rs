@@ -0,0 +1,25 @@
+use super::*;
+
+// Shape checks for the Church encodings. Each one normalizes first, with
+// `EvalConfig::default()`, and then matches up to alpha. They never panic:
+// open terms are fine, and a term with no normal form within the default
+// budget is none of these. The encodings overlap, so `0`, `false` and the
+// empty list, all `λa b. b`, satisfy every predicate.
+impl Expr {
+    pub fn is_church_numeral(&self) -> bool {
+        self.recognize(|nf| nf.to_numeral().is_ok())
+    }
+
+    pub fn is_church_bool(&self) -> bool {
+        self.recognize(|nf| nf.to_bool().is_some())
+    }
+
+    pub fn is_church_list(&self) -> bool {
+        self.recognize(|nf| nf.to_vec().is_some())
+    }
+
+    fn recognize(&self, shape: impl Fn(&Expr) -> bool) -> bool {
+        self.normalize_with(&EvalConfig::default())
+            .is_ok_and(|nf| shape(&nf))
+    }
+}
@@ -0,0 +1,973 @@
+use super::*;
+use terms::{ChurchNumeral, EvalError};
+
+mod graph;
+mod krivine;
+mod nbe;
+#[cfg(feature = "std")]
+mod shared;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct NotARedex(pub Path);
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Divergence {
+    // The term after `step` passes had already been seen.
+    Cycle { step: usize },
+    OutOfSteps,
+    // The term after `step` passes was deeper than the depth limit.
+    TooDeep { step: usize },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Strategy {
+    // Leftmost-outermost redex first; finds a normal form whenever one exists.
+    Normal,
+    // Leftmost-innermost redex first, so arguments are reduced before use.
+    Applicative,
+    // Normal order, except that `succ`, `add`, `mul` and `exp` applied to
+    // numerals are computed in Rust and re-encoded in a single step. Reaches
+    // the same normal forms as `Normal`, in far fewer steps on arithmetic.
+    NumeralAware,
+}
+
+// Decides which redex to contract next; `Strategy` implements it for the
+// built-in orders, and `ByName` and `ByValue` for weak reduction. Implement
+// it to try another one, e.g. smallest redex first, and run it with
+// `reduce_once_by` or `normalize_by`.
+pub trait RedexSelector {
+    // The path of the redex to contract in `e`, or `None` to stop.
+    fn select(&self, e: &Expr) -> Option<Path>;
+
+    // Contracts the redex `select` chose. Beta reduction, unless the
+    // selector has redexes of its own, as `NumeralAware` does.
+    fn contract(&self, e: &Expr, path: &Path) -> Option<Expr> {
+        e.beta_reduce_at(path).ok()
+    }
+}
+
+impl RedexSelector for Strategy {
+    fn select(&self, e: &Expr) -> Option<Path> {
+        e.next_redex(*self)
+    }
+
+    fn contract(&self, e: &Expr, path: &Path) -> Option<Expr> {
+        match self {
+            Strategy::NumeralAware => WithArithmetic::new(&Strategy::Normal).contract(e, path),
+            _ => e.beta_reduce_at(path).ok(),
+        }
+    }
+}
+
+// Call-by-name: the leftmost-outermost redex that is not under a lambda.
+// Weak, so `λx. (λy. y) x` is already done.
+pub struct ByName;
+
+impl RedexSelector for ByName {
+    fn select(&self, e: &Expr) -> Option<Path> {
+        e.weak_redex_paths().into_iter().next()
+    }
+}
+
+// Call-by-value: the leftmost-innermost redex that is not under a lambda,
+// so an argument is reduced before it is passed.
+pub struct ByValue;
+
+impl RedexSelector for ByValue {
+    fn select(&self, e: &Expr) -> Option<Path> {
+        innermost(e.weak_redex_paths())
+    }
+}
+
+// The first of `paths` that no other one extends, i.e. the leftmost of the
+// innermost redexes.
+fn innermost(mut paths: Vec<Path>) -> Option<Path> {
+    let index = paths
+        .iter()
+        .position(|p| !paths.iter().any(|q| q.len() > p.len() && q.starts_with(p)))?;
+    Some(paths.swap_remove(index))
+}
+
+// Any selector with `NumeralAware`'s arithmetic in front, for
+// `EvalConfig::numeral_aware`. Build one per normalization: it holds the
+// operators it looks for.
+struct WithArithmetic<'a> {
+    inner: &'a dyn RedexSelector,
+    ops: Arithmetic,
+}
+
+impl<'a> WithArithmetic<'a> {
+    fn new(inner: &'a dyn RedexSelector) -> WithArithmetic<'a> {
+        WithArithmetic {
+            inner,
+            ops: Arithmetic::new(),
+        }
+    }
+}
+
+impl RedexSelector for WithArithmetic<'_> {
+    fn select(&self, e: &Expr) -> Option<Path> {
+        self.ops.redex(e).or_else(|| self.inner.select(e))
+    }
+
+    fn contract(&self, e: &Expr, path: &Path) -> Option<Expr> {
+        e.replace_at(path, &|e| Some(self.ops.eval(e)?.to_church()))
+            .or_else(|| self.inner.contract(e, path))
+    }
+}
+
+// The de Bruijn forms of `succ`, `add`, `mul` and `exp`, so recognizing
+// one at a node is a single walk over it.
+struct Arithmetic {
+    succ: DeBrujin,
+    add: DeBrujin,
+    mul: DeBrujin,
+    exp: DeBrujin,
+}
+
+impl Arithmetic {
+    fn new() -> Arithmetic {
+        Arithmetic {
+            succ: terms::succ().debrujin(),
+            add: terms::add().debrujin(),
+            mul: terms::mul().debrujin(),
+            exp: terms::exp().debrujin(),
+        }
+    }
+
+    // The value of `succ n`, `add n m`, `mul n m` or `exp n m` on numerals.
+    // `exp n 0` is left to beta reduction: it reduces to `λx. x`, which is
+    // not the numeral one.
+    fn eval(&self, e: &Expr) -> Option<u32> {
+        let Expr::App(m, arg) = e else {
+            return None;
+        };
+        let b = arg.numeral_spine()?;
+        if self.succ.is_form_of(m) {
+            return b.checked_add(1);
+        }
+        let Expr::App(op, arg) = m.as_ref() else {
+            return None;
+        };
+        let a = arg.numeral_spine()?;
+        if self.add.is_form_of(op) {
+            a.checked_add(b)
+        } else if self.mul.is_form_of(op) {
+            a.checked_mul(b)
+        } else if b > 0 && self.exp.is_form_of(op) {
+            a.checked_pow(b)
+        } else {
+            None
+        }
+    }
+
+    // The outermost, leftmost subterm of `e` that `eval` can compute.
+    fn redex(&self, e: &Expr) -> Option<Path> {
+        let mut found = None;
+        e.walk_paths(|path, e| {
+            if self.eval(e).is_some() {
+                found = Some(path.clone());
+            }
+            found.is_some()
+        });
+        found
+    }
+}
+
+impl Strategy {
+    pub const ALL: [Strategy; 3] = [
+        Strategy::Normal,
+        Strategy::Applicative,
+        Strategy::NumeralAware,
+    ];
+}
+
+impl Expr {
+    // Whether both sides have the same normal form up to alpha, or `None`
+    // when either has none within the default budget of normal-order steps.
+    // Free variables must match by name.
+    pub fn equivalence(&self, other: &Expr) -> Option<bool> {
+        let max_steps = EvalConfig::default().max_steps;
+        self.equivalent_within(other, max_steps, Strategy::Normal)
+    }
+
+    // `equivalence` without a budget: hangs when either side has no normal
+    // form.
+    pub fn equivalence_unbounded(&self, other: &Expr) -> bool {
+        self.full_reduction().alpha_eq(&other.full_reduction())
+    }
+
+    // `equivalence` that gives up with `None` when either side has no normal
+    // form within `max_steps` steps of `strategy`.
+    pub fn equivalent_within(
+        &self,
+        other: &Expr,
+        max_steps: usize,
+        strategy: Strategy,
+    ) -> Option<bool> {
+        let a = self.reduce_with(strategy, max_steps)?;
+        let b = other.reduce_with(strategy, max_steps)?;
+        Some(a.alpha_eq(&b))
+    }
+
+    // One parallel pass: reduces both sides of every application, then
+    // contracts it if the function side became a lambda.
+    pub fn reduction(&self) -> Expr {
+        self.reduction_with(&mut |id, avoid| freshen(id, avoid))
+    }
+
+    fn reduction_with(&self, fresh: &mut Fresh) -> Expr {
+        let mut todo = vec![Build::Visit(self)];
+        let mut done = vec![];
+        while let Some(task) = todo.pop() {
+            match task {
+                Build::Visit(Expr::Lam(id, body)) => {
+                    todo.push(Build::Lam(id.clone()));
+                    todo.push(Build::Visit(body));
+                }
+                Build::Visit(Expr::App(m, n)) => {
+                    todo.push(Build::App);
+                    todo.push(Build::Visit(n));
+                    todo.push(Build::Visit(m));
+                }
+                Build::Visit(var) => done.push(var.clone()),
+                Build::Lam(id) => {
+                    let body = done.pop().unwrap();
+                    done.push(Expr::Lam(id, Box::new(body)));
+                }
+                Build::App => {
+                    let n = done.pop().unwrap();
+                    let m = done.pop().unwrap();
+                    done.push(match m {
+                        Expr::Lam(id, body) => body.substitution_with(&id, &n, fresh),
+                        m => Expr::App(Box::new(m), Box::new(n)),
+                    });
+                }
+            }
+        }
+        done.pop().unwrap()
+    }
+
+    pub fn full_reduction(&self) -> Expr {
+        let mut expr = self.clone();
+        let mut expr2 = expr.reduction();
+        while !expr.alpha_eq(&expr2) {
+            expr = expr2;
+            expr2 = expr.reduction();
+        }
+        expr
+    }
+
+    // `full_reduction` naming renamed binders `v1`, `v2`, ... from `supply`
+    // instead of priming them.
+    pub fn full_reduction_with(&self, supply: &mut FreshSupply) -> Expr {
+        let mut fresh = |_: &str, _: &[Id]| supply.fresh();
+        let mut expr = self.clone();
+        let mut expr2 = expr.reduction_with(&mut fresh);
+        while !expr.alpha_eq(&expr2) {
+            expr = expr2;
+            expr2 = expr.reduction_with(&mut fresh);
+        }
+        expr
+    }
+
+    // `full_reduction` giving up with `None` when no normal form turns up
+    // within `max_steps` passes. A term that a pass leaves unchanged without
+    // being normal, like Ω, is `None` too.
+    pub fn full_reduction_bounded(&self, max_steps: usize) -> Option<Expr> {
+        let mut expr = self.clone();
+        for _ in 0..max_steps {
+            if expr.is_normal() {
+                return Some(expr);
+            }
+            expr = expr.reduction();
+        }
+        None
+    }
+
+    // `full_reduction_bounded` that also remembers every term it passes
+    // through, up to alpha, and stops with `Cycle` as soon as one comes back,
+    // so a loop is told apart from slow progress. Ω, which reduces to itself,
+    // is a cycle here rather than a fixed point. Costs a copy of every
+    // intermediate term.
+    #[cfg(feature = "std")]
+    pub fn full_reduction_bounded_checked(&self, max_steps: usize) -> Result<Expr, Divergence> {
+        let mut seen = Visited::default();
+        let mut expr = self.clone();
+        for step in 0..max_steps {
+            if expr.is_normal() {
+                return Ok(expr);
+            }
+            if !seen.insert(&expr) {
+                return Err(Divergence::Cycle { step });
+            }
+            expr = expr.reduction();
+        }
+        Err(Divergence::OutOfSteps)
+    }
+
+    // `full_reduction` that stops once `deadline` has passed, returning the
+    // term reached so far and whether it is in normal form. The clock is only
+    // read every `DEADLINE_CHECK_INTERVAL` passes.
+    #[cfg(feature = "std")]
+    pub fn reduce_until_deadline(&self, deadline: std::time::Instant) -> (Expr, bool) {
+        const DEADLINE_CHECK_INTERVAL: usize = 16;
+        let mut expr = self.clone();
+        let mut steps = 0;
+        while !expr.is_normal() {
+            if steps % DEADLINE_CHECK_INTERVAL == 0 && std::time::Instant::now() >= deadline {
+                return (expr, false);
+            }
+            expr = expr.reduction();
+            steps += 1;
+        }
+        (expr, true)
+    }
+
+    // True when the term contains no redex. Unlike the fixed-point check in
+    // `full_reduction`, this does not mistake `Ω` for a normal form.
+    pub fn is_normal(&self) -> bool {
+        match self {
+            Expr::Lam(_, body) => body.is_normal(),
+            Expr::App(m, n) => {
+                !matches!(m.as_ref(), Expr::Lam(..)) && m.is_normal() && n.is_normal()
+            }
+            Expr::Var(_) => true,
+        }
+    }
+
+    // Normal-order reduction of the head only, stopping at the first lambda
+    // or at a variable applied to (unreduced) arguments.
+    pub fn whnf(&self) -> Expr {
+        match self {
+            Expr::App(m, n) => match m.whnf() {
+                Expr::Lam(id, body) => body.substitution(&id, n).whnf(),
+                m => Expr::App(Box::new(m), n.clone()),
+            },
+            _ => self.clone(),
+        }
+    }
+
+    pub fn krivine_whnf(&self) -> Expr {
+        krivine::State::new(self).run().to_expr()
+    }
+
+    // Largest number of enclosing binders that are still referenced at some
+    // node, i.e. are live there. A binder shadowed by an inner one with the
+    // same name can no longer be referenced, so it is not counted.
+    pub fn max_live_binders(&self) -> usize {
+        self.live_binders_with(&vec![]).1
+    }
+
+    fn live_binders_with(&self, scope: &Vec<Id>) -> (Vec<Id>, usize) {
+        let (fv, max) = match self {
+            Expr::Lam(id, expr) => {
+                let mut scope = scope.clone();
+                scope.push(id.clone());
+                let (mut fv, max) = expr.live_binders_with(&scope);
+                fv.retain(|x| x != id);
+                (fv, max)
+            }
+            Expr::App(expr1, expr2) => {
+                let (mut fv1, max1) = expr1.live_binders_with(scope);
+                let (fv2, max2) = expr2.live_binders_with(scope);
+                for id in fv2 {
+                    if !fv1.contains(&id) {
+                        fv1.push(id);
+                    }
+                }
+                (fv1, max1.max(max2))
+            }
+            Expr::Var(id) => (vec![id.clone()], 0),
+        };
+        let live = fv.iter().filter(|x| scope.contains(x)).count();
+        (fv, max.max(live))
+    }
+
+    // Same as `full_reduction`, but also reports the largest number of live
+    // binders seen on any intermediate term. Gives up with `None` as soon as
+    // that number exceeds `limit`.
+    pub fn full_reduction_live(&self, limit: usize) -> Option<(Expr, usize)> {
+        let mut expr = self.clone();
+        let mut max = expr.max_live_binders();
+        loop {
+            if max > limit {
+                return None;
+            }
+            let expr2 = expr.reduction();
+            if expr.alpha_eq(&expr2) {
+                return Some((expr, max));
+            }
+            max = max.max(expr2.max_live_binders());
+            expr = expr2;
+        }
+    }
+
+    // Normal-order normalization over an `Rc`-shared copy of the term. Closed
+    // subterms are normalized once per de Bruijn form and reused.
+    #[cfg(feature = "std")]
+    pub fn normalize_shared(&self) -> Expr {
+        self.normalize_shared_counted().0
+    }
+
+    // `normalize_shared`, with the cache and its counts of shared and copied
+    // subterms.
+    #[cfg(feature = "std")]
+    pub(crate) fn normalize_shared_counted(&self) -> (Expr, shared::Cache) {
+        let mut cache = shared::Cache::default();
+        let term = shared::Term::from_expr(self);
+        let nf = shared::normalize(&term, &mut cache).to_expr();
+        (nf, cache)
+    }
+
+    // Normal form by call-by-need graph reduction: an argument is reduced
+    // at most once however many times it is used. Loops forever on terms
+    // without one.
+    pub fn normalize_lazy(&self) -> Expr {
+        self.normalize_lazy_with(|_| {})
+    }
+
+    // `normalize_lazy`, calling `on_contract` with the binder of every
+    // lambda it contracts.
+    pub fn normalize_lazy_with(&self, on_contract: impl FnMut(&Id)) -> Expr {
+        graph::Machine::new(on_contract).normalize(self)
+    }
+
+    // Normalization by evaluation: evaluate into closures, then read the
+    // resulting value back as a term. No substitution or renaming involved.
+    pub fn normalize_nbe(&self) -> Expr {
+        nbe::reify(&nbe::eval(self, &nbe::Env::empty()))
+    }
+
+    // Beta-normalizes, then eta-expands every subterm to its expected arity:
+    // the whole term to `arity`, and every occurrence of a variable to the
+    // most arguments that name is applied to anywhere in the normal form.
+    // `f` at arity 1 becomes `λx. f x`, and in `λf x. g f (f x)` the `f`
+    // passed to `g` becomes `λx'. f x'`. Without types, a name stands for
+    // all of its binders, so shadowed names share one arity.
+    pub fn eta_long(&self, arity: usize) -> Expr {
+        let nf = self.normalize_nbe();
+        let mut arities = vec![];
+        nf.applied_arities(&mut arities);
+        let mut avoid = nf.all_variables();
+        nf.eta_expand(arity, &arities, &mut avoid)
+    }
+
+    // For each variable applied somewhere, the most arguments it gets.
+    fn applied_arities(&self, arities: &mut Vec<(Id, usize)>) {
+        if let Expr::Lam(_, body) = self {
+            return body.applied_arities(arities);
+        }
+        let (head, args) = self.to_app_vec();
+        match head {
+            Expr::Var(id) => match arities.iter_mut().find(|(x, _)| x == id) {
+                Some((_, n)) => *n = (*n).max(args.len()),
+                None => arities.push((id.clone(), args.len())),
+            },
+            head => head.applied_arities(arities),
+        }
+        for arg in args {
+            arg.applied_arities(arities);
+        }
+    }
+
+    // `eta_long` on a normal form: expands it to take at least `arity`
+    // arguments, and its head to take all of its own.
+    fn eta_expand(&self, arity: usize, arities: &[(Id, usize)], avoid: &mut Vec<Id>) -> Expr {
+        let mut binders = vec![];
+        let mut body = self;
+        while let Expr::Lam(id, inner) = body {
+            binders.push(id.clone());
+            body = inner;
+        }
+        let (head, args) = body.to_app_vec();
+        let missing = match head {
+            Expr::Var(id) => arities
+                .iter()
+                .find(|(x, _)| x == id)
+                .map_or(0, |(_, n)| n - args.len()),
+            _ => 0,
+        };
+        let mut body = args.iter().fold(head.clone(), |acc, arg| {
+            acc.apply(&arg.eta_expand(0, arities, avoid))
+        });
+        for _ in 0..missing.max(arity.saturating_sub(binders.len())) {
+            let id = if avoid.iter().any(|x| x == "x") {
+                freshen("x", avoid)
+            } else {
+                "x".to_string()
+            };
+            body = body.apply(&Expr::Var(id.clone()));
+            binders.push(id.clone());
+            avoid.push(id);
+        }
+        binders
+            .into_iter()
+            .rev()
+            .fold(body, |acc, id| Expr::Lam(id, Box::new(acc)))
+    }
+
+    // Contracts every eta redex `λx. m x` with `x` not free in `m`. Bodies go
+    // first, so a redex exposed by contracting inside its body is caught too.
+    pub fn eta_reduce(&self) -> Expr {
+        match self {
+            Expr::Lam(x, body) => match body.eta_reduce() {
+                Expr::App(m, n) if is_eta_redex(x, &m, &n) => *m,
+                body => Expr::Lam(x.clone(), Box::new(body)),
+            },
+            Expr::App(m, n) => Expr::App(Box::new(m.eta_reduce()), Box::new(n.eta_reduce())),
+            Expr::Var(_) => self.clone(),
+        }
+    }
+
+    // True when no `λx. m x` with `x` not free in `m` occurs anywhere, the
+    // counterpart of `is_normal` for eta.
+    pub fn is_eta_normal(&self) -> bool {
+        match self {
+            Expr::Lam(x, body) => {
+                let redex = matches!(body.as_ref(), Expr::App(m, n) if is_eta_redex(x, m, n));
+                !redex && body.is_eta_normal()
+            }
+            Expr::App(m, n) => m.is_eta_normal() && n.is_eta_normal(),
+            Expr::Var(_) => true,
+        }
+    }
+
+    // The beta-eta normal form: normal order within `max_steps`
+    // contractions, then `eta_reduce`, which cannot create a beta redex in
+    // a beta-normal term.
+    pub fn beta_eta_normalize(&self, max_steps: usize) -> Result<Expr, EvalError> {
+        let cfg = EvalConfig::default().max_steps(max_steps);
+        Ok(self.normalize_with(&cfg)?.eta_reduce())
+    }
+
+    // Equality up to beta and eta, so `λx. f x` equals `f`: both sides have
+    // alpha-equal `beta_eta_normalize` forms within the default budget.
+    // `false` when either side runs out of steps.
+    pub fn eta_eq(&self, other: &Expr) -> bool {
+        let max_steps = EvalConfig::default().max_steps;
+        match (
+            self.beta_eta_normalize(max_steps),
+            other.beta_eta_normalize(max_steps),
+        ) {
+            (Ok(a), Ok(b)) => a.alpha_eq(&b),
+            _ => false,
+        }
+    }
+
+    // Contracts exactly the redex at `path`.
+    pub fn beta_reduce_at(&self, path: &Path) -> Result<Expr, NotARedex> {
+        self.replace_at(path, &|e| match e {
+            Expr::App(m, n) => match m.as_ref() {
+                Expr::Lam(id, body) => Some(body.substitution(id, n)),
+                _ => None,
+            },
+            _ => None,
+        })
+        .ok_or_else(|| NotARedex(path.clone()))
+    }
+
+    // Paths of every redex, outermost first and left to right.
+    pub fn redex_paths(&self) -> Vec<Path> {
+        let mut paths = vec![];
+        self.walk_paths(|path, e| {
+            if let Expr::App(m, _) = e {
+                if let Expr::Lam(..) = m.as_ref() {
+                    paths.push(path.clone());
+                }
+            }
+            false
+        });
+        paths
+    }
+
+    // Normal-order normalization that treats the definitions in `normal_defs`
+    // as opaque, already-normal values. A definition is only unfolded when it
+    // is the head of an application, where its body is needed to make
+    // progress; elsewhere it stays a name and is never normalized again.
+    #[cfg(feature = "std")]
+    pub fn normalize_modulo(&self, normal_defs: &Env) -> Expr {
+        self.normalize_modulo_with(normal_defs, &mut vec![])
+    }
+
+    #[cfg(feature = "std")]
+    fn normalize_modulo_with(&self, defs: &Env, bound: &mut Vec<Id>) -> Expr {
+        let mut head = self.clone();
+        let mut args = vec![];
+        loop {
+            match head {
+                Expr::App(m, n) => {
+                    args.push(*n);
+                    head = *m;
+                }
+                Expr::Lam(id, body) if !args.is_empty() => {
+                    head = body.substitution(&id, &args.pop().unwrap());
+                }
+                Expr::Var(ref id)
+                    if !args.is_empty() && !bound.contains(id) && defs.contains_key(id) =>
+                {
+                    head = defs[id].clone();
+                }
+                _ => break,
+            }
+        }
+        let head = match head {
+            Expr::Lam(id, body) => {
+                bound.push(id.clone());
+                let body = body.normalize_modulo_with(defs, bound);
+                bound.pop();
+                Expr::Lam(id, Box::new(body))
+            }
+            head => head,
+        };
+        args.iter().rev().fold(head, |acc, arg| {
+            acc.apply(&arg.normalize_modulo_with(defs, bound))
+        })
+    }
+
+    // Conservative divergence check: `true` means the term has no normal
+    // form, `false` means unknown. Takes a few normal-order steps and looks
+    // for a leftmost-outermost redex that contracts to itself, possibly
+    // applied to more arguments, as `Ω` and `(λx. x x x)(λx. x x x)` do. Normal
+    // order would then contract that redex forever, and since it finds a
+    // normal form whenever one exists, there is none.
+    pub fn detect_omega(&self) -> bool {
+        const LIGHT_STEPS: usize = 8;
+        let mut expr = self.clone();
+        for _ in 0..LIGHT_STEPS {
+            let Some(path) = expr.redex_paths().into_iter().next() else {
+                return false;
+            };
+            let redex = expr.subterm(&path).unwrap();
+            let contracted = redex.beta_reduce_at(&vec![]).unwrap();
+            let mut head = &contracted;
+            loop {
+                if head.alpha_eq(redex) {
+                    return true;
+                }
+                match head {
+                    Expr::App(m, _) => head = m,
+                    _ => break,
+                }
+            }
+            expr = expr.beta_reduce_at(&path).unwrap();
+        }
+        false
+    }
+
+    // Contracts exactly the one redex `strategy` picks, or `None` in normal
+    // form. The stepping primitive everything strategy-driven is built on.
+    pub fn reduce_once(&self, strategy: Strategy) -> Option<Expr> {
+        self.reduce_once_by(&strategy)
+    }
+
+    // `reduce_once` in the order of any selector.
+    pub fn reduce_once_by(&self, selector: &dyn RedexSelector) -> Option<Expr> {
+        selector.contract(self, &selector.select(self)?)
+    }
+
+    // Where `reduce_once` contracts.
+    pub fn next_redex(&self, strategy: Strategy) -> Option<Path> {
+        match strategy {
+            Strategy::Normal => self.redex_paths().into_iter().next(),
+            Strategy::Applicative => innermost(self.redex_paths()),
+            Strategy::NumeralAware => WithArithmetic::new(&Strategy::Normal).select(self),
+        }
+    }
+
+    // `redex_paths` without the ones under a lambda.
+    fn weak_redex_paths(&self) -> Vec<Path> {
+        let mut paths = self.redex_paths();
+        paths.retain(|path| !path.contains(&Dir::LamBody));
+        paths
+    }
+
+    // Up to `max_steps` contractions, recorded one entry per step; each
+    // entry's `before` is the previous entry's `after`.
+    pub fn trace_json(&self, max_steps: usize, strategy: Strategy) -> Vec<TraceEntry> {
+        let with_arithmetic;
+        let selector: &dyn RedexSelector = if strategy == Strategy::NumeralAware {
+            with_arithmetic = WithArithmetic::new(&Strategy::Normal);
+            &with_arithmetic
+        } else {
+            &strategy
+        };
+        let mut trace = vec![];
+        let mut current = self.clone();
+        for step in 1..=max_steps {
+            let Some(path) = selector.select(&current) else {
+                break;
+            };
+            let next = selector.contract(&current, &path).unwrap();
+            let normal = next.is_normal();
+            trace.push(TraceEntry {
+                step,
+                path,
+                before: current.to_string(),
+                after: next.to_string(),
+                normal,
+            });
+            current = next;
+            if normal {
+                break;
+            }
+        }
+        trace
+    }
+
+    // The terms reached by contracting one redex at a time.
+    pub fn reduction_steps(&self, strategy: Strategy) -> ReductionSteps {
+        ReductionSteps {
+            current: self.clone(),
+            strategy,
+        }
+    }
+
+    // Normal form under `strategy`, or `None` if it takes more than
+    // `max_steps` contractions. There is no depth limit.
+    pub fn reduce_with(&self, strategy: Strategy, max_steps: usize) -> Option<Expr> {
+        let cfg = EvalConfig::default()
+            .strategy(strategy)
+            .max_steps(max_steps);
+        self.normalize_with(&cfg).ok()
+    }
+
+    // `reduce_with` that also stops with `TooDeep` once a term along the way
+    // is more than `max_depth` deep. Unlike the step budget this bounds the
+    // size of a single term, so a runaway term is caught before the parts
+    // that recurse on it (renaming under binders, `Clone`, `Drop`) can
+    // overflow the stack. `usize::MAX` disables the check, and its cost of a
+    // walk over the term per step.
+    pub fn reduce_with_depth(
+        &self,
+        strategy: Strategy,
+        max_steps: usize,
+        max_depth: usize,
+    ) -> Result<Expr, Divergence> {
+        let cfg = EvalConfig::default()
+            .strategy(strategy)
+            .max_steps(max_steps)
+            .max_depth(max_depth);
+        self.normalize_with(&cfg).map_err(|e| match e {
+            EvalError::TooDeep { step } => Divergence::TooDeep { step },
+            _ => Divergence::OutOfSteps,
+        })
+    }
+
+    // Contracts one redex at a time as `cfg` says until the term is normal.
+    // The other strategy-driven normalizers are this with some options
+    // fixed. Fails with `Diverged` when `cfg.max_steps` contractions are not
+    // enough.
+    pub fn normalize_with(&self, cfg: &EvalConfig) -> Result<Expr, EvalError> {
+        self.normalize_counted_with(cfg, &mut |_, _| {})
+            .map(|(expr, _)| expr)
+    }
+
+    // `normalize_with` that calls `on_step` with the term and the path of
+    // the redex just before each contraction, so a long reduction can be
+    // watched without keeping its trace.
+    pub fn normalize_observed(
+        &self,
+        cfg: &EvalConfig,
+        on_step: &mut dyn FnMut(&Expr, &Path),
+    ) -> Result<Expr, EvalError> {
+        self.normalize_counted_with(cfg, on_step)
+            .map(|(expr, _)| expr)
+    }
+
+    // The normal form in normal order and how many contractions it took,
+    // e.g. to compare what two encodings of the same operation cost.
+    pub fn normalize_counted(&self, max_steps: usize) -> Result<(Expr, usize), EvalError> {
+        self.normalize_counted_with(&EvalConfig::default().max_steps(max_steps), &mut |_, _| {})
+    }
+
+    // `normalize_with` with `selector` picking the redexes in place of
+    // `cfg.strategy`; every other option of `cfg` applies. Fails with
+    // `BadSelector` when the selector cannot contract a path it selected.
+    pub fn normalize_by(
+        &self,
+        selector: &dyn RedexSelector,
+        cfg: &EvalConfig,
+    ) -> Result<Expr, EvalError> {
+        self.run(selector, cfg, &mut |_, _| {})
+            .map(|(expr, _)| expr)
+    }
+
+    fn normalize_counted_with(
+        &self,
+        cfg: &EvalConfig,
+        on_step: &mut dyn FnMut(&Expr, &Path),
+    ) -> Result<(Expr, usize), EvalError> {
+        // Normal order with the arithmetic option, so the operators are
+        // built once for the whole run rather than on every step.
+        if cfg.strategy == Strategy::NumeralAware {
+            let cfg = cfg.strategy(Strategy::Normal).numeral_aware(true);
+            return self.run(&Strategy::Normal, &cfg, on_step);
+        }
+        self.run(&cfg.strategy, cfg, on_step)
+    }
+
+    // The loop behind `normalize_with`, with every option of `cfg` but the
+    // strategy.
+    fn run(
+        &self,
+        selector: &dyn RedexSelector,
+        cfg: &EvalConfig,
+        on_step: &mut dyn FnMut(&Expr, &Path),
+    ) -> Result<(Expr, usize), EvalError> {
+        let with_arithmetic;
+        let selector: &dyn RedexSelector = if cfg.numeral_aware {
+            with_arithmetic = WithArithmetic::new(selector);
+            &with_arithmetic
+        } else {
+            selector
+        };
+        let mut seen = Visited::default();
+        let mut expr = self.clone();
+        for step in 0..=cfg.max_steps {
+            if cfg.max_depth != usize::MAX && expr.depth() > cfg.max_depth {
+                return Err(EvalError::TooDeep { step });
+            }
+            if cfg.detect_cycles && !seen.insert(&expr) {
+                return Err(EvalError::Cycle { step });
+            }
+            let Some(path) = selector.select(&expr) else {
+                return Ok((expr, step));
+            };
+            if step == cfg.max_steps {
+                break;
+            }
+            on_step(&expr, &path);
+            match selector.contract(&expr, &path) {
+                Some(next) => expr = next,
+                None => return Err(EvalError::BadSelector { step }),
+            }
+        }
+        Err(EvalError::Diverged)
+    }
+}
+
+// The terms a reduction has passed through, up to alpha, for cycle
+// detection. Each one is stored in canonical form, so a repeat is a single
+// lookup.
+#[derive(Default)]
+struct Visited {
+    #[cfg(feature = "std")]
+    seen: std::collections::HashSet<Expr>,
+    // No hash sets without `std`; the printed canonical forms are ordered.
+    #[cfg(not(feature = "std"))]
+    seen: alloc::collections::BTreeSet<String>,
+}
+
+impl Visited {
+    // False when `e` was seen before.
+    fn insert(&mut self, e: &Expr) -> bool {
+        let key = e.to_canonical();
+        #[cfg(not(feature = "std"))]
+        let key = key.to_string();
+        self.seen.insert(key)
+    }
+}
+
+// Whether `λx. m n` is an eta redex: `n` is `x`, and `x` is not free in `m`.
+fn is_eta_redex(x: &Id, m: &Expr, n: &Expr) -> bool {
+    matches!(n, Expr::Var(y) if y == x) && !m.occurs_free(x)
+}
+
+// Options for `normalize_with`, set with the builder methods of the same
+// names, e.g. `EvalConfig::default().max_steps(100).detect_cycles(true)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EvalConfig {
+    pub strategy: Strategy,
+    pub max_steps: usize,
+    // Stop with `Cycle` when a term comes back up to alpha, as Ω does.
+    // Costs a copy of every intermediate term.
+    pub detect_cycles: bool,
+    // Compute arithmetic on numerals in one step, as `NumeralAware` does,
+    // whatever `strategy` is.
+    pub numeral_aware: bool,
+    // Stop with `TooDeep` past this depth, as `reduce_with_depth` does.
+    pub max_depth: usize,
+}
+
+// Normal order, 10 000 steps and no other checks: `full_reduction`, except
+// that it gives up instead of hanging. The budget is the library's default
+// wherever a function takes none.
+impl Default for EvalConfig {
+    fn default() -> EvalConfig {
+        EvalConfig {
+            strategy: Strategy::Normal,
+            max_steps: 10_000,
+            detect_cycles: false,
+            numeral_aware: false,
+            max_depth: usize::MAX,
+        }
+    }
+}
+
+impl EvalConfig {
+    pub fn strategy(mut self, strategy: Strategy) -> EvalConfig {
+        self.strategy = strategy;
+        self
+    }
+
+    pub fn max_steps(mut self, max_steps: usize) -> EvalConfig {
+        self.max_steps = max_steps;
+        self
+    }
+
+    pub fn detect_cycles(mut self, detect_cycles: bool) -> EvalConfig {
+        self.detect_cycles = detect_cycles;
+        self
+    }
+
+    pub fn numeral_aware(mut self, numeral_aware: bool) -> EvalConfig {
+        self.numeral_aware = numeral_aware;
+        self
+    }
+
+    pub fn max_depth(mut self, max_depth: usize) -> EvalConfig {
+        self.max_depth = max_depth;
+        self
+    }
+}
+
+// Normalizes every term under every strategy and checks that the ones that
+// terminate within `max_steps` agree up to alpha, as Church-Rosser
+// guarantees. A disagreement points at a reducer bug.
+pub fn strategies_agree_on(terms: &[Expr], max_steps: usize) -> bool {
+    terms.iter().all(|term| {
+        let normal_forms: Vec<Expr> = Strategy::ALL
+            .iter()
+            .filter_map(|&s| term.reduce_with(s, max_steps))
+            .collect();
+        normal_forms.windows(2).all(|w| w[0].alpha_eq(&w[1]))
+    })
+}
+
+// One contraction of `trace_json`: the redex at `path` in `before` was
+// contracted, giving `after`, which is a normal form if `normal` is set.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TraceEntry {
+    pub step: usize,
+    pub path: Path,
+    pub before: String,
+    pub after: String,
+    pub normal: bool,
+}
+
+pub struct ReductionSteps {
+    current: Expr,
+    strategy: Strategy,
+}
+
+impl Iterator for ReductionSteps {
+    type Item = Expr;
+
+    fn next(&mut self) -> Option<Expr> {
+        self.current = self.current.reduce_once(self.strategy)?;
+        Some(self.current.clone())
+    }
+}
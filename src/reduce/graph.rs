@@ -0,0 +1,192 @@
+// Call-by-need graph reduction. A contraction does not copy its argument
+// into the body: every occurrence of the bound variable points at the one
+// argument node, and reducing that node overwrites it with the result, so
+// the work is done at most once and seen by every occurrence.
+//
+// Variables are told apart by a stamp rather than by name, so splicing an
+// argument under a binder can never capture. Only lambda bodies are copied
+// on contraction, and only the parts that mention the bound variable.
+// Bodies themselves are never reduced in place; to normalize under a lambda
+// it is applied to a fresh variable. So every variable met during reduction
+// belongs to a binder that has not been contracted yet, and a copied lambda
+// can keep the stamp of the one it was copied from.
+
+use super::*;
+use alloc::collections::BTreeMap;
+use alloc::rc::Rc;
+use core::cell::RefCell;
+
+type Graph = Rc<RefCell<Node>>;
+
+// `stamp` is 0 for free variables and unique to its binder otherwise.
+#[derive(Clone, PartialEq, Eq)]
+struct Name {
+    id: Id,
+    stamp: usize,
+}
+
+#[derive(Clone)]
+enum Node {
+    Lam(Name, Graph),
+    App(Graph, Graph),
+    Var(Name),
+    // What a reduced application was overwritten with.
+    Ind(Graph),
+}
+
+fn node(n: Node) -> Graph {
+    Rc::new(RefCell::new(n))
+}
+
+pub struct Machine<F> {
+    stamps: usize,
+    // Called with the binder of every lambda that is contracted.
+    on_contract: F,
+}
+
+impl<F: FnMut(&Id)> Machine<F> {
+    pub fn new(on_contract: F) -> Machine<F> {
+        Machine {
+            stamps: 0,
+            on_contract,
+        }
+    }
+
+    pub fn normalize(&mut self, e: &Expr) -> Expr {
+        let g = self.build(e, &mut vec![]);
+        let mut avoid = e.fv();
+        self.read_back(&g, &mut BTreeMap::new(), &mut avoid)
+    }
+
+    fn fresh(&mut self, id: &Id) -> Name {
+        self.stamps += 1;
+        Name {
+            id: id.clone(),
+            stamp: self.stamps,
+        }
+    }
+
+    fn build(&mut self, e: &Expr, scope: &mut Vec<Name>) -> Graph {
+        match e {
+            Expr::Lam(id, body) => {
+                let name = self.fresh(id);
+                scope.push(name.clone());
+                let body = self.build(body, scope);
+                scope.pop();
+                node(Node::Lam(name, body))
+            }
+            Expr::App(m, n) => {
+                let m = self.build(m, scope);
+                node(Node::App(m, self.build(n, scope)))
+            }
+            Expr::Var(id) => {
+                let name = scope.iter().rev().find(|x| x.id == *id).cloned();
+                node(Node::Var(name.unwrap_or(Name {
+                    id: id.clone(),
+                    stamp: 0,
+                })))
+            }
+        }
+    }
+
+    // Reduces `g` to weak head normal form, overwriting every application
+    // contracted on the way, and returns the node it ended on.
+    fn whnf(&mut self, g: &Graph) -> Graph {
+        let mut g = g.clone();
+        loop {
+            let current = g.borrow().clone();
+            match current {
+                Node::Ind(next) => g = next,
+                Node::App(m, n) => {
+                    let head = self.whnf(&m);
+                    let Node::Lam(x, body) = head.borrow().clone() else {
+                        return g;
+                    };
+                    (self.on_contract)(&x.id);
+                    let result = instantiate(&body, &x, &n);
+                    *g.borrow_mut() = Node::Ind(result.clone());
+                    g = result;
+                }
+                Node::Lam(..) | Node::Var(_) => return g,
+            }
+        }
+    }
+
+    // `names` maps the stamps of the variables introduced while going under
+    // binders to the names they are printed with; `avoid` holds those names
+    // and the free variables, so no binder shadows anything.
+    fn read_back(
+        &mut self,
+        g: &Graph,
+        names: &mut BTreeMap<usize, Id>,
+        avoid: &mut Vec<Id>,
+    ) -> Expr {
+        let g = self.whnf(g);
+        let current = g.borrow().clone();
+        match current {
+            Node::Lam(x, body) => {
+                let id = if avoid.contains(&x.id) {
+                    freshen(&x.id, avoid)
+                } else {
+                    x.id.clone()
+                };
+                let v = self.fresh(&id);
+                let opened = instantiate(&body, &x, &node(Node::Var(v.clone())));
+                names.insert(v.stamp, id.clone());
+                avoid.push(id.clone());
+                let body = self.read_back(&opened, names, avoid);
+                avoid.pop();
+                Expr::Lam(id, Box::new(body))
+            }
+            Node::App(m, n) => {
+                let m = self.read_back(&m, names, avoid);
+                m.apply(&self.read_back(&n, names, avoid))
+            }
+            Node::Var(x) => Expr::Var(names.get(&x.stamp).cloned().unwrap_or(x.id)),
+            Node::Ind(_) => unreachable!("whnf follows indirections"),
+        }
+    }
+}
+
+// `body[x := arg]`, sharing `arg` and every part of `body` that does not
+// mention `x`.
+fn instantiate(body: &Graph, x: &Name, arg: &Graph) -> Graph {
+    let mut copies = BTreeMap::new();
+    copy(body, x, arg, &mut copies).unwrap_or_else(|| body.clone())
+}
+
+// The copy of `g` with `x` replaced, or `None` if `x` does not occur in it.
+// `copies` remembers the answer per node, so shared nodes stay shared.
+fn copy(
+    g: &Graph,
+    x: &Name,
+    arg: &Graph,
+    copies: &mut BTreeMap<*const RefCell<Node>, Option<Graph>>,
+) -> Option<Graph> {
+    let key = Rc::as_ptr(g);
+    if let Some(done) = copies.get(&key) {
+        return done.clone();
+    }
+    let current = g.borrow().clone();
+    let result = match current {
+        Node::Var(y) if y == *x => Some(arg.clone()),
+        Node::Var(_) => None,
+        Node::Ind(next) => copy(&next, x, arg, copies),
+        // Copies of one lambda share its stamp, and may end up nested when a
+        // shared argument is applied to itself; the inner one shadows.
+        Node::Lam(y, _) if y == *x => None,
+        Node::Lam(y, body) => copy(&body, x, arg, copies).map(|body| node(Node::Lam(y, body))),
+        Node::App(m, n) => {
+            let m2 = copy(&m, x, arg, copies);
+            let n2 = copy(&n, x, arg, copies);
+            if m2.is_none() && n2.is_none() {
+                None
+            } else {
+                let m = m2.unwrap_or(m);
+                Some(node(Node::App(m, n2.unwrap_or(n))))
+            }
+        }
+    };
+    copies.insert(key, result.clone());
+    result
+}
@@ -0,0 +1,141 @@
+use super::*;
+use alloc::rc::Rc;
+
+// Terms with de Bruijn indices; binders keep their source name as a hint
+// for reading the result back.
+enum Code {
+    Lam(Id, Rc<Code>),
+    App(Rc<Code>, Rc<Code>),
+    Var(usize),
+    Free(Id),
+}
+
+impl Code {
+    fn compile(expr: &Expr, ctx: &mut Vec<Id>) -> Rc<Code> {
+        Rc::new(match expr {
+            Expr::Lam(id, body) => {
+                ctx.push(id.clone());
+                let body = Code::compile(body, ctx);
+                ctx.pop();
+                Code::Lam(id.clone(), body)
+            }
+            Expr::App(m, n) => Code::App(Code::compile(m, ctx), Code::compile(n, ctx)),
+            Expr::Var(id) => match ctx.iter().rev().position(|x| x == id) {
+                Some(i) => Code::Var(i),
+                None => Code::Free(id.clone()),
+            },
+        })
+    }
+}
+
+#[derive(Clone)]
+struct Closure {
+    code: Rc<Code>,
+    env: Env,
+}
+
+#[derive(Clone)]
+struct Env(Option<Rc<(Closure, Env)>>);
+
+impl Env {
+    fn get(&self, i: usize) -> &Closure {
+        let mut env = self;
+        let mut i = i;
+        loop {
+            let node = env.0.as_ref().expect("index out of environment");
+            if i == 0 {
+                return &node.0;
+            }
+            env = &node.1;
+            i -= 1;
+        }
+    }
+}
+
+pub struct State {
+    code: Rc<Code>,
+    env: Env,
+    stack: Vec<Closure>,
+    avoid: Vec<Id>,
+}
+
+impl State {
+    pub fn new(expr: &Expr) -> State {
+        State {
+            code: Code::compile(expr, &mut vec![]),
+            env: Env(None),
+            stack: vec![],
+            avoid: expr.fv(),
+        }
+    }
+
+    // Performs one machine transition, returning `false` once the state is
+    // in weak head normal form.
+    pub fn step(&mut self) -> bool {
+        match self.code.clone().as_ref() {
+            Code::App(m, n) => {
+                self.stack.push(Closure {
+                    code: n.clone(),
+                    env: self.env.clone(),
+                });
+                self.code = m.clone();
+                true
+            }
+            Code::Lam(_, body) => match self.stack.pop() {
+                Some(arg) => {
+                    self.env = Env(Some(Rc::new((arg, self.env.clone()))));
+                    self.code = body.clone();
+                    true
+                }
+                None => false,
+            },
+            Code::Var(i) => {
+                let c = self.env.get(*i).clone();
+                self.code = c.code;
+                self.env = c.env;
+                true
+            }
+            Code::Free(_) => false,
+        }
+    }
+
+    pub fn run(mut self) -> State {
+        while self.step() {}
+        self
+    }
+
+    pub fn to_expr(&self) -> Expr {
+        let head = read_back(&self.code, &self.env, &mut vec![], &self.avoid);
+        self.stack.iter().rev().fold(head, |acc, c| {
+            acc.apply(&read_back(&c.code, &c.env, &mut vec![], &self.avoid))
+        })
+    }
+}
+
+// Rebuilds a named term from a closure, substituting its environment.
+// Binder names are kept distinct from enclosing binders and free
+// variables, so nothing is captured.
+fn read_back(code: &Code, env: &Env, scope: &mut Vec<Id>, avoid: &Vec<Id>) -> Expr {
+    match code {
+        Code::Lam(hint, body) => {
+            let mut id = hint.clone();
+            while scope.contains(&id) || avoid.contains(&id) {
+                id.push('\'');
+            }
+            scope.push(id.clone());
+            let body = read_back(body, env, scope, avoid);
+            scope.pop();
+            Expr::Lam(id, Box::new(body))
+        }
+        Code::App(m, n) => Expr::App(
+            Box::new(read_back(m, env, scope, avoid)),
+            Box::new(read_back(n, env, scope, avoid)),
+        ),
+        Code::Var(i) if *i < scope.len() => Expr::Var(scope[scope.len() - 1 - i].clone()),
+        Code::Var(i) => {
+            let c = env.get(i - scope.len());
+            read_back(&c.code, &c.env, &mut vec![], avoid)
+        }
+        Code::Free(id) => Expr::Var(id.clone()),
+    }
+}
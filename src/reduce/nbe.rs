@@ -0,0 +1,141 @@
+use super::*;
+use alloc::rc::Rc;
+
+#[derive(Clone)]
+pub enum Value<'a> {
+    Lam(Closure<'a>),
+    Neutral(Rc<Neutral<'a>>),
+}
+
+#[derive(Clone)]
+pub struct Closure<'a> {
+    env: Env<'a>,
+    id: &'a Id,
+    body: &'a Expr,
+}
+
+pub enum Neutral<'a> {
+    // Bound variable introduced while reifying, by binder depth (level).
+    Level(usize),
+    Free(Id),
+    App(Rc<Neutral<'a>>, Value<'a>),
+}
+
+#[derive(Clone)]
+pub struct Env<'a>(Option<Rc<(Id, Value<'a>, Env<'a>)>>);
+
+impl<'a> Env<'a> {
+    pub fn empty() -> Env<'a> {
+        Env(None)
+    }
+
+    pub fn extend(&self, id: &Id, value: Value<'a>) -> Env<'a> {
+        Env(Some(Rc::new((id.clone(), value, self.clone()))))
+    }
+
+    pub fn lookup(&self, id: &Id) -> Option<&Value<'a>> {
+        let mut env = self;
+        while let Some(node) = &env.0 {
+            if &node.0 == id {
+                return Some(&node.1);
+            }
+            env = &node.2;
+        }
+        None
+    }
+}
+
+pub fn eval<'a>(expr: &'a Expr, env: &Env<'a>) -> Value<'a> {
+    match expr {
+        Expr::Lam(id, body) => Value::Lam(Closure {
+            env: env.clone(),
+            id,
+            body,
+        }),
+        Expr::App(m, n) => apply(eval(m, env), eval(n, env)),
+        Expr::Var(id) => match env.lookup(id) {
+            Some(value) => value.clone(),
+            None => Value::Neutral(Rc::new(Neutral::Free(id.clone()))),
+        },
+    }
+}
+
+fn apply<'a>(f: Value<'a>, arg: Value<'a>) -> Value<'a> {
+    match f {
+        Value::Lam(c) => eval(c.body, &c.env.extend(c.id, arg)),
+        Value::Neutral(n) => Value::Neutral(Rc::new(Neutral::App(n, arg))),
+    }
+}
+
+// Normal form with bound variables still as levels; binders keep the
+// name they had in the source as a hint for the final naming.
+enum Nf {
+    Lam(Id, Box<Nf>),
+    App(Box<Nf>, Box<Nf>),
+    Level(usize),
+    Free(Id),
+}
+
+fn quote(value: &Value, depth: usize) -> Nf {
+    match value {
+        Value::Lam(c) => {
+            let var = Value::Neutral(Rc::new(Neutral::Level(depth)));
+            let body = eval(c.body, &c.env.extend(c.id, var));
+            Nf::Lam(c.id.clone(), Box::new(quote(&body, depth + 1)))
+        }
+        Value::Neutral(n) => quote_neutral(n, depth),
+    }
+}
+
+fn quote_neutral(n: &Neutral, depth: usize) -> Nf {
+    match n {
+        Neutral::Level(l) => Nf::Level(*l),
+        Neutral::Free(id) => Nf::Free(id.clone()),
+        Neutral::App(m, arg) => Nf::App(
+            Box::new(quote_neutral(m, depth)),
+            Box::new(quote(arg, depth)),
+        ),
+    }
+}
+
+fn free_names(nf: &Nf, acc: &mut Vec<Id>) {
+    match nf {
+        Nf::Lam(_, body) => free_names(body, acc),
+        Nf::App(m, n) => {
+            free_names(m, acc);
+            free_names(n, acc);
+        }
+        Nf::Level(_) => {}
+        Nf::Free(id) => acc.push(id.clone()),
+    }
+}
+
+// Every binder gets a name distinct from the enclosing binders and from
+// the free variables, so the named term cannot capture anything.
+fn name(nf: &Nf, scope: &mut Vec<Id>, avoid: &Vec<Id>) -> Expr {
+    match nf {
+        Nf::Lam(hint, body) => {
+            let mut id = hint.clone();
+            while scope.contains(&id) || avoid.contains(&id) {
+                id.push('\'');
+            }
+            scope.push(id.clone());
+            let body = name(body, scope, avoid);
+            scope.pop();
+            Expr::Lam(id, Box::new(body))
+        }
+        Nf::App(m, n) => Expr::App(
+            Box::new(name(m, scope, avoid)),
+            Box::new(name(n, scope, avoid)),
+        ),
+        Nf::Level(l) => Expr::Var(scope[*l].clone()),
+        Nf::Free(id) => Expr::Var(id.clone()),
+    }
+}
+
+pub fn reify(value: &Value) -> Expr {
+    let nf = quote(value, 0);
+    let mut avoid = vec![];
+    free_names(&nf, &mut avoid);
+    name(&nf, &mut vec![], &avoid)
+}
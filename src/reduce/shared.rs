@@ -0,0 +1,164 @@
+use super::*;
+use alloc::rc::Rc;
+use std::collections::HashMap;
+
+pub enum Term {
+    Lam(Id, Rc<Term>),
+    App(Rc<Term>, Rc<Term>),
+    Var(Id),
+}
+
+// Normal forms of the closed subterms seen so far, by de Bruijn form. Also
+// counts, for the tests, the subterms substitution `shared` with its input
+// instead of copying, and the nodes it `built`.
+#[derive(Default)]
+pub struct Cache {
+    normal_forms: HashMap<DeBrujin, Rc<Term>>,
+    pub shared: usize,
+    pub built: usize,
+}
+
+impl Term {
+    pub fn from_expr(expr: &Expr) -> Rc<Term> {
+        Rc::new(match expr {
+            Expr::Lam(id, expr) => Term::Lam(id.clone(), Term::from_expr(expr)),
+            Expr::App(m, n) => Term::App(Term::from_expr(m), Term::from_expr(n)),
+            Expr::Var(id) => Term::Var(id.clone()),
+        })
+    }
+
+    pub fn to_expr(&self) -> Expr {
+        match self {
+            Term::Lam(id, t) => Expr::Lam(id.clone(), Box::new(t.to_expr())),
+            Term::App(m, n) => Expr::App(Box::new(m.to_expr()), Box::new(n.to_expr())),
+            Term::Var(id) => Expr::Var(id.clone()),
+        }
+    }
+
+    fn occurs_free(&self, x: &Id) -> bool {
+        match self {
+            Term::Lam(id, t) => id != x && t.occurs_free(x),
+            Term::App(m, n) => m.occurs_free(x) || n.occurs_free(x),
+            Term::Var(id) => id == x,
+        }
+    }
+
+    // Every name in the term, bound or free.
+    fn variables(&self) -> Vec<Id> {
+        match self {
+            Term::Lam(id, t) => {
+                let mut ids = t.variables();
+                ids.push(id.clone());
+                ids
+            }
+            Term::App(m, n) => {
+                let mut ids = m.variables();
+                ids.append(&mut n.variables());
+                ids
+            }
+            Term::Var(id) => vec![id.clone()],
+        }
+    }
+
+    fn fv(&self) -> Vec<Id> {
+        match self {
+            Term::Lam(id, t) => {
+                let mut fv = t.fv();
+                fv.retain(|x| x != id);
+                fv
+            }
+            Term::App(m, n) => {
+                let mut fv = m.fv();
+                fv.append(&mut n.fv());
+                fv
+            }
+            Term::Var(id) => vec![id.clone()],
+        }
+    }
+
+    // `None` when the term has free variables, so it cannot be cached.
+    fn debrujin_with(&self, ctx: &mut Vec<Id>) -> Option<DeBrujin> {
+        Some(match self {
+            Term::Lam(id, t) => {
+                ctx.push(id.clone());
+                let body = t.debrujin_with(ctx);
+                ctx.pop();
+                DeBrujin::Lam(Box::new(body?))
+            }
+            Term::App(m, n) => DeBrujin::App(
+                Box::new(m.debrujin_with(ctx)?),
+                Box::new(n.debrujin_with(ctx)?),
+            ),
+            Term::Var(id) => {
+                let index = ctx.iter().rev().position(|x| x == id)?;
+                DeBrujin::Var(index as u32)
+            }
+        })
+    }
+}
+
+// Capture-avoiding `t[x := s]`. Subterms without a free `x` are shared
+// with the input instead of being rebuilt.
+fn substitution(t: &Rc<Term>, x: &Id, s: &Rc<Term>, cache: &mut Cache) -> Rc<Term> {
+    if !t.occurs_free(x) {
+        cache.shared += 1;
+        return t.clone();
+    }
+    match t.as_ref() {
+        Term::Var(_) => {
+            cache.shared += 1;
+            s.clone()
+        }
+        Term::App(m, n) => {
+            let m = substitution(m, x, s, cache);
+            let n = substitution(n, x, s, cache);
+            cache.built += 1;
+            Rc::new(Term::App(m, n))
+        }
+        Term::Lam(id, body) => {
+            cache.built += 1;
+            if s.fv().contains(id) {
+                let mut avoid = body.variables();
+                avoid.extend(s.variables());
+                avoid.push(x.clone());
+                let nid = freshen(id, &avoid);
+                let var = Rc::new(Term::Var(nid.clone()));
+                let renamed = substitution(body, id, &var, cache);
+                Rc::new(Term::Lam(nid, substitution(&renamed, x, s, cache)))
+            } else {
+                Rc::new(Term::Lam(id.clone(), substitution(body, x, s, cache)))
+            }
+        }
+    }
+}
+
+fn whnf(t: &Rc<Term>, cache: &mut Cache) -> Rc<Term> {
+    match t.as_ref() {
+        Term::App(m, n) => {
+            let m = whnf(m, cache);
+            if let Term::Lam(id, body) = m.as_ref() {
+                let contracted = substitution(body, id, n, cache);
+                whnf(&contracted, cache)
+            } else {
+                Rc::new(Term::App(m, n.clone()))
+            }
+        }
+        _ => t.clone(),
+    }
+}
+
+pub fn normalize(t: &Rc<Term>, cache: &mut Cache) -> Rc<Term> {
+    let key = t.debrujin_with(&mut vec![]);
+    if let Some(nf) = key.as_ref().and_then(|k| cache.normal_forms.get(k)) {
+        return nf.clone();
+    }
+    let nf = match whnf(t, cache).as_ref() {
+        Term::Lam(id, body) => Rc::new(Term::Lam(id.clone(), normalize(body, cache))),
+        Term::App(m, n) => Rc::new(Term::App(normalize(m, cache), normalize(n, cache))),
+        Term::Var(id) => Rc::new(Term::Var(id.clone())),
+    };
+    if let Some(k) = key {
+        cache.normal_forms.insert(k, nf.clone());
+    }
+    nf
+}
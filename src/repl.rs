@@ -0,0 +1,251 @@
+use std::fmt;
+use std::io::{self, BufRead, Write};
+
+use lcrs::{terms, EvalConfig, Expr, Id, Program, Strategy};
+
+// Writes its replies to `out`: stdout at the prompt, a buffer in the tests.
+struct Repl<W: Write> {
+    // In definition order; each body already has earlier definitions resolved.
+    defs: Vec<(Id, Expr)>,
+    current: Option<Expr>,
+    strategy: Strategy,
+    budget: usize,
+    out: W,
+}
+
+impl<W: Write> Repl<W> {
+    fn new(out: W) -> Repl<W> {
+        Repl {
+            defs: vec![],
+            current: None,
+            strategy: Strategy::Normal,
+            budget: EvalConfig::default().max_steps,
+            out,
+        }
+    }
+
+    fn say(&mut self, reply: impl fmt::Display) {
+        writeln!(self.out, "{reply}").unwrap();
+    }
+
+    // Replaces free occurrences of defined names by their bodies, latest
+    // definition first, so a body never picks up a later definition. Names
+    // left free after that fall back to the library terms.
+    fn resolve(&self, expr: Expr) -> Expr {
+        let expr = self
+            .defs
+            .iter()
+            .rev()
+            .fold(expr, |acc, (id, body)| acc.substitution(id, body));
+        let mut free = expr.fv();
+        free.sort();
+        free.dedup();
+        free.iter().fold(expr, |acc, id| match terms::builtin(id) {
+            Some(body) => acc.substitution(id, &body),
+            None => acc,
+        })
+    }
+
+    // Runs `let` definitions and evaluates the trailing term, if any.
+    fn run(&mut self, src: &str) {
+        let program = match src.parse::<Program>() {
+            Ok(program) => program,
+            Err(err) => {
+                self.say(format!("parse error: {err}"));
+                return;
+            }
+        };
+        for (id, value) in program.defs {
+            let value = self.resolve(value);
+            self.defs.push((id, value));
+        }
+        if let Some(body) = program.body {
+            let body = self.resolve(body);
+            self.evaluate(body);
+        }
+    }
+
+    fn show_value(&mut self, expr: &Expr) {
+        if let Ok(n) = expr.to_numeral() {
+            self.say(format!("  = {n}"));
+        } else if let Some(b) = expr.to_bool() {
+            self.say(format!("  = {b}"));
+        }
+    }
+
+    fn evaluate(&mut self, expr: Expr) {
+        match expr.reduce_with(self.strategy, self.budget) {
+            Some(nf) => {
+                self.say(&nf);
+                self.show_value(&nf);
+            }
+            None => self.say(format!("no normal form within {} steps", self.budget)),
+        }
+        self.current = Some(expr);
+    }
+
+    fn step(&mut self) {
+        let Some(expr) = self.current.clone() else {
+            self.say("no current term");
+            return;
+        };
+        match expr.reduce_once(self.strategy) {
+            Some(next) => {
+                self.say(&next);
+                self.current = Some(next);
+            }
+            None => {
+                self.say(&expr);
+                self.say("  (normal form)");
+                self.show_value(&expr);
+            }
+        }
+    }
+
+    fn load(&mut self, path: &str) {
+        match std::fs::read_to_string(path) {
+            Ok(src) => {
+                for line in src.lines() {
+                    self.handle(line);
+                }
+            }
+            Err(err) => self.say(format!("cannot read {path}: {err}")),
+        }
+    }
+
+    // Returns `false` when the session should end.
+    fn handle(&mut self, line: &str) -> bool {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return true;
+        }
+        let (command, arg) = line.split_once(' ').unwrap_or((line, ""));
+        match command {
+            ":quit" | ":q" => return false,
+            ":normal" => self.strategy = Strategy::Normal,
+            ":applicative" => self.strategy = Strategy::Applicative,
+            ":step" | ":s" => self.step(),
+            ":load" => self.load(arg.trim()),
+            ":budget" => match arg.trim().parse() {
+                Ok(n) => self.budget = n,
+                Err(_) => self.say("expected a number of steps"),
+            },
+            ":strategy" => self.say(format!("{:?}", self.strategy)),
+            ":builtins" => self.say(terms::builtin_names().join(" ")),
+            ":show" => match self.current.clone() {
+                Some(expr) => self.say(expr),
+                None => self.say("no current term"),
+            },
+            ":help" => self.say(
+                "let name = term | term | :step | :show | :normal | :applicative | :strategy | :budget n | :load file | :builtins | :quit"
+            ),
+            _ if command.starts_with(':') => self.say(format!("unknown command {command}")),
+            _ => self.run(line),
+        }
+        true
+    }
+}
+
+fn main() {
+    let mut repl = Repl::new(io::stdout());
+    let stdin = io::stdin();
+    loop {
+        print!("λ> ");
+        io::stdout().flush().unwrap();
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).unwrap() == 0 || !repl.handle(&line) {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Feeds `lines` to a fresh session and returns everything it printed.
+    fn session(lines: &[&str]) -> String {
+        let mut repl = Repl::new(vec![]);
+        for line in lines {
+            assert!(repl.handle(line));
+        }
+        String::from_utf8(repl.out).unwrap()
+    }
+
+    #[test]
+    fn definitions_are_used_by_later_terms() {
+        let out = session(&["let two = λf x. f (f x)", "let four = add two two", "four"]);
+        assert_eq!(out, "λf x. f (f (f (f x)))\n  = 4\n");
+    }
+
+    #[test]
+    fn resolve_prefers_definitions_over_builtins() {
+        let mut repl = Repl::new(vec![]);
+        repl.handle("let succ = λn. n");
+        let resolved = repl.resolve("succ (pred 3)".parse().unwrap());
+        assert_eq!(resolved.fv(), Vec::<Id>::new());
+        assert_eq!(resolved.reduce_to_numeral(1_000), Ok(2));
+    }
+
+    #[test]
+    fn step_walks_to_the_normal_form() {
+        let out = session(&[":step", "(λx. x) ((λy. y) true)", ":step", ":step", ":step"]);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "no current term",
+                "λx y. x",
+                "  = true",
+                "(λy. y) λx y. x",
+                "λx y. x",
+                "λx y. x",
+                "  (normal form)",
+                "  = true",
+            ]
+        );
+    }
+
+    #[test]
+    fn strategy_commands_switch_the_order() {
+        let out = session(&[
+            ":strategy",
+            ":applicative",
+            ":strategy",
+            ":normal",
+            ":strategy",
+        ]);
+        assert_eq!(out, "Normal\nApplicative\nNormal\n");
+        // `t i Ω` only has a normal form in normal order.
+        let lazy = "(λx y. x) (λz. z) ((λx. x x) (λx. x x))";
+        let out = session(&[":budget 50", ":applicative", lazy, ":normal", lazy]);
+        assert_eq!(out, "no normal form within 50 steps\nλz. z\n");
+    }
+
+    #[test]
+    fn unknown_commands_and_bad_input_are_reported() {
+        let out = session(&[":frobnicate", ":budget lots", ":show", "λx y x"]);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(
+            lines[..3],
+            [
+                "unknown command :frobnicate",
+                "expected a number of steps",
+                "no current term"
+            ]
+        );
+        assert!(lines[3].starts_with("parse error: expected '.' after binder"));
+        assert!(!Repl::new(vec![]).handle(":quit"));
+    }
+
+    #[test]
+    fn load_runs_every_line_of_a_file() {
+        let path = std::env::temp_dir().join(format!("lcrs-repl-{}.lc", std::process::id()));
+        std::fs::write(&path, "# numerals\nlet three = succ 2\n\nmul three three\n").unwrap();
+        let out = session(&[&format!(":load {}", path.display())]);
+        std::fs::remove_file(&path).unwrap();
+        assert!(out.ends_with("  = 9\n"), "{out}");
+        let missing = session(&[":load /nonexistent/lcrs.lc"]);
+        assert!(missing.starts_with("cannot read /nonexistent/lcrs.lc: "));
+    }
+}
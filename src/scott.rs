@@ -0,0 +1,111 @@
+// Scott encodings represent data by its case analysis rather than its fold:
+// a Scott numeral `succ n` is `λz s. s n`, holding its predecessor directly,
+// where the Church numeral `n` is `λf x. f (f ... x)`. So `pred` and pattern
+// matching take a constant number of contractions (Church `pred` rebuilds the
+// whole numeral), but iteration needs explicit recursion through a fixed-point
+// combinator. Pick Church numerals for arithmetic, Scott for data you take
+// apart.
+
+use super::*;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct NotAScottNumeral;
+
+pub fn zero() -> Expr {
+    Expr::lambda("z", Expr::lambda("s", Expr::var("z")))
+}
+
+pub fn succ() -> Expr {
+    // λn z s. s n
+    Expr::lambda(
+        "n",
+        Expr::lambda(
+            "z",
+            Expr::lambda("s", Expr::var("s").apply(&Expr::var("n"))),
+        ),
+    )
+}
+
+pub fn case() -> Expr {
+    // λn z s. n z s
+    Expr::lambda(
+        "n",
+        Expr::lambda(
+            "z",
+            Expr::lambda(
+                "s",
+                Expr::var("n").apply(&Expr::var("z")).apply(&Expr::var("s")),
+            ),
+        ),
+    )
+}
+
+pub fn pred() -> Expr {
+    // λn. n zero (λp. p)
+    Expr::lambda(
+        "n",
+        Expr::var("n")
+            .apply(&zero())
+            .apply(&Expr::lambda("p", Expr::var("p"))),
+    )
+}
+
+pub fn nat(n: u32) -> Expr {
+    (0..n).fold(zero(), |acc, _| succ().apply(&acc).full_reduction())
+}
+
+pub fn nil() -> Expr {
+    Expr::lambda("n", Expr::lambda("c", Expr::var("n")))
+}
+
+pub fn cons() -> Expr {
+    // λh t n c. c h t
+    Expr::lambda(
+        "h",
+        Expr::lambda(
+            "t",
+            Expr::lambda(
+                "n",
+                Expr::lambda(
+                    "c",
+                    Expr::var("c").apply(&Expr::var("h")).apply(&Expr::var("t")),
+                ),
+            ),
+        ),
+    )
+}
+
+// Expects a normal form: `λz s. z` or `λz s. s m` for a numeral `m`.
+pub fn decode(expr: &Expr) -> Result<u32, NotAScottNumeral> {
+    let mut count = 0;
+    let mut expr = expr;
+    loop {
+        let Expr::Lam(z, body) = expr else {
+            return Err(NotAScottNumeral);
+        };
+        let Expr::Lam(s, body) = body.as_ref() else {
+            return Err(NotAScottNumeral);
+        };
+        if z == s {
+            return Err(NotAScottNumeral);
+        }
+        match body.as_ref() {
+            Expr::Var(v) if v == z => return Ok(count),
+            Expr::App(head, m)
+                if matches!(head.as_ref(), Expr::Var(v) if v == s)
+                    && !m.fv().contains(z)
+                    && !m.fv().contains(s) =>
+            {
+                count += 1;
+                expr = m;
+            }
+            _ => return Err(NotAScottNumeral),
+        }
+    }
+}
+
+impl Expr {
+    pub fn scott_to_u32(&self) -> Result<u32, scott::NotAScottNumeral> {
+        scott::decode(self)
+    }
+}
@@ -0,0 +1,283 @@
+// Simple types, checked bidirectionally over the untyped `Expr`. A lambda
+// being checked against `A → B` takes `A` for its binder; a lambda whose type
+// has to be inferred looks its binder up in the context of assumptions, which
+// plays the part of the annotation. Nothing here affects reduction.
+
+use super::*;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Type {
+    Base(String),
+    Arrow(Box<Type>, Box<Type>),
+}
+
+impl Type {
+    pub fn base(name: &str) -> Type {
+        Type::Base(name.to_string())
+    }
+
+    pub fn arrow(from: Type, to: Type) -> Type {
+        Type::Arrow(Box::new(from), Box::new(to))
+    }
+}
+
+// Arrows associate to the right: `(a → b) → a → b`.
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Type::Base(name) => write!(f, "{name}"),
+            Type::Arrow(from, to) if matches!(**from, Type::Arrow(..)) => {
+                write!(f, "({from}) → {to}")
+            }
+            Type::Arrow(from, to) => write!(f, "{from} → {to}"),
+        }
+    }
+}
+
+// Assumptions about free variables and lambda binders; later entries
+// shadow earlier ones.
+pub type TypeCtx = Vec<(Id, Type)>;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum TypeError {
+    Unbound(Id),
+    // A lambda in inference position whose binder has no assumption.
+    MissingAnnotation(Id),
+    NotAFunction {
+        path: Path,
+        found: Type,
+    },
+    // `path` is the subterm that has the wrong type, e.g. the argument
+    // of a mismatched application.
+    Mismatch {
+        path: Path,
+        expected: Type,
+        found: Type,
+    },
+}
+
+impl Expr {
+    pub fn type_check(&self, ctx: &TypeCtx) -> Result<Type, TypeError> {
+        infer(self, &mut ctx.clone(), &mut vec![])
+    }
+
+    pub fn check_type(&self, ctx: &TypeCtx, ty: &Type) -> Result<(), TypeError> {
+        check(self, ty, &mut ctx.clone(), &mut vec![])
+    }
+}
+
+// A principal type. Every name in `vars` is universally quantified; for
+// closed untyped terms that is every base type in `ty`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Scheme {
+    pub vars: Vec<String>,
+    pub ty: Type,
+}
+
+impl fmt::Display for Scheme {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.ty)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum InferError {
+    Unbound(Id),
+    // Two types that must be equal but cannot be unified. Without type
+    // constants this only happens when the occurs check fails, as in
+    // `λx. x x`, where `left` would have to contain itself.
+    Unify { left: Type, right: Type },
+}
+
+impl Expr {
+    // Algorithm W for a closed term: a fresh type variable for every
+    // binder, unification at every application, then generalization of
+    // what is left, with variables renamed `a`, `b`, ... in order of
+    // appearance.
+    pub fn infer(&self) -> Result<Scheme, InferError> {
+        let mut w = W::default();
+        let ty = w.infer(self, &mut vec![])?;
+        let ty = w.resolve(&ty);
+        let mut vars = vec![];
+        collect_vars(&ty, &mut vars);
+        let names: Vec<String> = (0..vars.len()).map(var_name).collect();
+        let renaming: Vec<(String, Type)> = vars
+            .iter()
+            .cloned()
+            .zip(names.iter().map(|n| Type::base(n)))
+            .collect();
+        Ok(Scheme {
+            vars: names,
+            ty: rename_vars(&ty, &renaming),
+        })
+    }
+}
+
+fn var_name(i: usize) -> String {
+    let letter = (b'a' + (i % 26) as u8) as char;
+    match i / 26 {
+        0 => letter.to_string(),
+        n => format!("{letter}{n}"),
+    }
+}
+
+fn collect_vars(ty: &Type, vars: &mut Vec<String>) {
+    match ty {
+        Type::Base(v) if !vars.contains(v) => vars.push(v.clone()),
+        Type::Base(_) => {}
+        Type::Arrow(from, to) => {
+            collect_vars(from, vars);
+            collect_vars(to, vars);
+        }
+    }
+}
+
+fn rename_vars(ty: &Type, renaming: &[(String, Type)]) -> Type {
+    match ty {
+        Type::Base(v) => renaming
+            .iter()
+            .find(|(x, _)| x == v)
+            .map_or_else(|| ty.clone(), |(_, to)| to.clone()),
+        Type::Arrow(from, to) => {
+            Type::arrow(rename_vars(from, renaming), rename_vars(to, renaming))
+        }
+    }
+}
+
+// Inference state: the next fresh variable and the substitution solved
+// so far. Type variables are `Base` types named `'1`, `'2`, ...; they
+// only live until `infer` renames them.
+#[derive(Default)]
+struct W {
+    next: u32,
+    subst: alloc::collections::BTreeMap<String, Type>,
+}
+
+impl W {
+    fn fresh(&mut self) -> Type {
+        self.next += 1;
+        Type::Base(format!("'{}", self.next))
+    }
+
+    // Applies the substitution all the way down.
+    fn resolve(&self, ty: &Type) -> Type {
+        match ty {
+            Type::Base(v) => match self.subst.get(v) {
+                Some(t) => self.resolve(t),
+                None => ty.clone(),
+            },
+            Type::Arrow(from, to) => Type::arrow(self.resolve(from), self.resolve(to)),
+        }
+    }
+
+    fn occurs(&self, v: &str, ty: &Type) -> bool {
+        match self.resolve(ty) {
+            Type::Base(w) => w == v,
+            Type::Arrow(from, to) => self.occurs(v, &from) || self.occurs(v, &to),
+        }
+    }
+
+    fn unify(&mut self, left: &Type, right: &Type) -> Result<(), InferError> {
+        match (self.resolve(left), self.resolve(right)) {
+            (Type::Base(a), Type::Base(b)) if a == b => Ok(()),
+            (Type::Base(v), ty) | (ty, Type::Base(v)) => {
+                if self.occurs(&v, &ty) {
+                    return Err(InferError::Unify {
+                        left: Type::Base(v),
+                        right: ty,
+                    });
+                }
+                self.subst.insert(v, ty);
+                Ok(())
+            }
+            (Type::Arrow(a1, b1), Type::Arrow(a2, b2)) => {
+                self.unify(&a1, &a2)?;
+                self.unify(&b1, &b2)
+            }
+        }
+    }
+
+    fn infer(&mut self, e: &Expr, env: &mut TypeCtx) -> Result<Type, InferError> {
+        match e {
+            Expr::Var(id) => lookup(env, id).ok_or_else(|| InferError::Unbound(id.clone())),
+            Expr::Lam(id, body) => {
+                let from = self.fresh();
+                env.push((id.clone(), from.clone()));
+                let to = self.infer(body, env);
+                env.pop();
+                Ok(Type::arrow(from, to?))
+            }
+            Expr::App(m, n) => {
+                let head = self.infer(m, env)?;
+                let arg = self.infer(n, env)?;
+                let result = self.fresh();
+                self.unify(&head, &Type::arrow(arg, result.clone()))?;
+                Ok(result)
+            }
+        }
+    }
+}
+
+fn lookup(ctx: &TypeCtx, id: &Id) -> Option<Type> {
+    ctx.iter()
+        .rev()
+        .find(|(x, _)| x == id)
+        .map(|(_, ty)| ty.clone())
+}
+
+fn infer(e: &Expr, ctx: &mut TypeCtx, path: &mut Path) -> Result<Type, TypeError> {
+    match e {
+        Expr::Var(id) => lookup(ctx, id).ok_or_else(|| TypeError::Unbound(id.clone())),
+        Expr::Lam(id, body) => {
+            let from = lookup(ctx, id).ok_or_else(|| TypeError::MissingAnnotation(id.clone()))?;
+            ctx.push((id.clone(), from.clone()));
+            path.push(Dir::LamBody);
+            let to = infer(body, ctx, path);
+            path.pop();
+            ctx.pop();
+            Ok(Type::arrow(from, to?))
+        }
+        Expr::App(m, n) => {
+            path.push(Dir::AppLeft);
+            let (from, to) = match infer(m, ctx, path)? {
+                Type::Arrow(from, to) => (from, to),
+                found => {
+                    return Err(TypeError::NotAFunction {
+                        path: path.clone(),
+                        found,
+                    })
+                }
+            };
+            path.pop();
+            path.push(Dir::AppRight);
+            check(n, &from, ctx, path)?;
+            path.pop();
+            Ok(*to)
+        }
+    }
+}
+
+fn check(e: &Expr, ty: &Type, ctx: &mut TypeCtx, path: &mut Path) -> Result<(), TypeError> {
+    match (e, ty) {
+        (Expr::Lam(id, body), Type::Arrow(from, to)) => {
+            ctx.push((id.clone(), (**from).clone()));
+            path.push(Dir::LamBody);
+            let result = check(body, to, ctx, path);
+            path.pop();
+            ctx.pop();
+            result
+        }
+        _ => {
+            let found = infer(e, ctx, path)?;
+            if &found == ty {
+                Ok(())
+            } else {
+                Err(TypeError::Mismatch {
+                    path: path.clone(),
+                    expected: ty.clone(),
+                    found,
+                })
+            }
+        }
+    }
+}
@@ -0,0 +1,1054 @@
+use super::*;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct NotANumeral;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct NotAPair;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum EvalError {
+    // No normal form within the step budget.
+    Diverged,
+    // The term after `step` steps had already been seen.
+    Cycle { step: usize },
+    // The term after `step` steps was deeper than the depth limit.
+    TooDeep { step: usize },
+    // A `RedexSelector` could not contract the path it selected at `step`.
+    BadSelector { step: usize },
+    NotANumeral,
+    NotABool,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum NotANumeralList {
+    NotAList,
+    // The index of the first element that is not a numeral.
+    Element(usize),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum NotAString {
+    NotAList,
+    // The index of the first element that is not a numeral.
+    NotANumeral(usize),
+    // The index of the first numeral above 127.
+    NotAscii(usize),
+}
+
+impl Expr {
+    pub fn church_zero() -> Expr {
+        0_u32.to_church()
+    }
+
+    pub fn church_one() -> Expr {
+        1_u32.to_church()
+    }
+
+    // Normalizes in normal order within `max_steps` contractions and decodes
+    // the result. Never hangs and never panics, unlike
+    // `full_reduction().to_numeral()`.
+    pub fn reduce_to_numeral(&self, max_steps: usize) -> Result<u32, EvalError> {
+        self.normalize_with(&EvalConfig::default().max_steps(max_steps))?
+            .to_numeral()
+            .map_err(|_| EvalError::NotANumeral)
+    }
+
+    pub fn reduce_to_bool(&self, max_steps: usize) -> Result<bool, EvalError> {
+        self.normalize_with(&EvalConfig::default().max_steps(max_steps))?
+            .to_bool()
+            .ok_or(EvalError::NotABool)
+    }
+
+    // Orders two terms by the numerals they normalize to, within the
+    // default budget that `to_numeral` uses.
+    pub fn cmp_numeral(&self, other: &Expr) -> Result<core::cmp::Ordering, NotANumeral> {
+        let a = self.to_numeral()?;
+        let b = other.to_numeral()?;
+        Ok(a.cmp(&b))
+    }
+
+    // Decodes a normal form `λl r. l x` as `Ok(x)` and `λl r. r x` as
+    // `Err(x)`.
+    pub fn to_either(&self) -> Option<Result<Expr, Expr>> {
+        let Expr::Lam(l, body) = self else {
+            return None;
+        };
+        let Expr::Lam(r, body) = body.as_ref() else {
+            return None;
+        };
+        let Expr::App(head, x) = body.as_ref() else {
+            return None;
+        };
+        let Expr::Var(v) = head.as_ref() else {
+            return None;
+        };
+        if l == r || x.fv().contains(l) || x.fv().contains(r) {
+            None
+        } else if v == l {
+            Some(Ok(x.as_ref().clone()))
+        } else if v == r {
+            Some(Err(x.as_ref().clone()))
+        } else {
+            None
+        }
+    }
+
+    // Decodes `λf. λx. f (f ... x)` whatever the binders are called, so
+    // numerals renamed during reduction are recognized too. The binders must
+    // differ: in `λf. λf. ...` both occurrences mean the inner one. A term
+    // of any other shape is normalized first, within the default
+    // `EvalConfig` budget, so a numeral that is one only up to beta, like
+    // `λf x. (λy. f y) x`, decodes the same whichever reducer left it so.
+    pub fn to_numeral(&self) -> Result<u32, NotANumeral> {
+        if let Some(n) = self.numeral_spine() {
+            return Ok(n);
+        }
+        if self.is_normal() {
+            return Err(NotANumeral);
+        }
+        self.normalize_with(&EvalConfig::default())
+            .ok()
+            .and_then(|normal| normal.numeral_spine())
+            .ok_or(NotANumeral)
+    }
+
+    // `to_numeral` without the normalization: only the literal spine.
+    pub(crate) fn numeral_spine(&self) -> Option<u32> {
+        let Expr::Lam(f, body) = self else {
+            return None;
+        };
+        let Expr::Lam(x, apps) = body.as_ref() else {
+            return None;
+        };
+        if f == x {
+            return None;
+        }
+        let mut count = 0;
+        let mut rest = apps.as_ref();
+        loop {
+            match rest.to_app_vec() {
+                (Expr::Var(id), args) if id == x && args.is_empty() => return Some(count),
+                (Expr::Var(id), args) if id == f && args.len() == 1 => {
+                    count += 1;
+                    rest = args[0];
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    // Normalizes a `tuple` and returns its components, which come out
+    // normalized too: `λf. f a b` under any binder name, with `f` free in
+    // neither component. A term with no normal form within the default
+    // `EvalConfig` budget is not a pair.
+    pub fn to_pair(&self) -> Result<(Expr, Expr), NotAPair> {
+        let Ok(Expr::Lam(f, body)) = self.normalize_with(&EvalConfig::default()) else {
+            return Err(NotAPair);
+        };
+        let Expr::App(m, b) = *body else {
+            return Err(NotAPair);
+        };
+        let Expr::App(head, a) = *m else {
+            return Err(NotAPair);
+        };
+        if !matches!(head.as_ref(), Expr::Var(id) if *id == f)
+            || a.occurs_free(&f)
+            || b.occurs_free(&f)
+        {
+            return Err(NotAPair);
+        }
+        Ok((*a, *b))
+    }
+
+    pub fn to_bool(&self) -> Option<bool> {
+        let Expr::Lam(x, body) = self else {
+            return None;
+        };
+        let Expr::Lam(y, body) = body.as_ref() else {
+            return None;
+        };
+        match body.as_ref() {
+            Expr::Var(z) if z == y => Some(false),
+            Expr::Var(z) if z == x => Some(true),
+            _ => None,
+        }
+    }
+
+    // Decodes a normal form `λc. λn. c h1 (c h2 (... n))` into its elements.
+    pub fn to_vec(&self) -> Option<Vec<Expr>> {
+        let Expr::Lam(c, body) = self else {
+            return None;
+        };
+        let Expr::Lam(n, body) = body.as_ref() else {
+            return None;
+        };
+        if c == n {
+            return None;
+        }
+        let mut elems = vec![];
+        let mut rest = body.as_ref();
+        loop {
+            match rest {
+                Expr::Var(id) if id == n => return Some(elems),
+                Expr::App(cell, tail) => {
+                    let Expr::App(cons, head) = cell.as_ref() else {
+                        return None;
+                    };
+                    let fv = head.fv();
+                    if !matches!(cons.as_ref(), Expr::Var(id) if id == c)
+                        || fv.contains(c)
+                        || fv.contains(n)
+                    {
+                        return None;
+                    }
+                    elems.push(head.as_ref().clone());
+                    rest = tail;
+                }
+                _ => return None,
+            }
+        }
+    }
+
+    // Normalizes a Church list of Church numerals and decodes every element.
+    // A term with no normal form within the default `EvalConfig` budget is
+    // not a list.
+    pub fn to_numeral_vec(&self) -> Result<Vec<u32>, NotANumeralList> {
+        self.normalize_with(&EvalConfig::default())
+            .map_err(|_| NotANumeralList::NotAList)?
+            .to_vec()
+            .ok_or(NotANumeralList::NotAList)?
+            .iter()
+            .enumerate()
+            .map(|(i, e)| e.to_numeral().map_err(|_| NotANumeralList::Element(i)))
+            .collect()
+    }
+
+    // The Church list of the code points of `s`, e.g. `"hi"` is `[104, 105]`.
+    // Only ASCII, which keeps every numeral below 128: otherwise `NotAscii`
+    // with the index of the first other character.
+    pub fn church_of_str(s: &str) -> Result<Expr, NotAString> {
+        if let Some(i) = s.chars().position(|c| !c.is_ascii()) {
+            return Err(NotAString::NotAscii(i));
+        }
+        let cell = |h: u8, t| Expr::var("c").apply(&h.to_church()).apply(&t);
+        let body = s.bytes().rev().fold(Expr::var("n"), |t, h| cell(h, t));
+        Ok(Expr::lambda("c", Expr::lambda("n", body)))
+    }
+
+    // Inverse of `church_of_str`: normalizes, then decodes the list.
+    pub fn to_church_string(&self) -> Result<String, NotAString> {
+        let codes = self.to_numeral_vec().map_err(|e| match e {
+            NotANumeralList::NotAList => NotAString::NotAList,
+            NotANumeralList::Element(i) => NotAString::NotANumeral(i),
+        })?;
+        codes
+            .iter()
+            .enumerate()
+            .map(|(i, &code)| match u8::try_from(code) {
+                Ok(byte) if byte.is_ascii() => Ok(char::from(byte)),
+                _ => Err(NotAString::NotAscii(i)),
+            })
+            .collect()
+    }
+
+    // `to_numeral_vec` for a list of Church booleans.
+    pub fn to_bool_vec(&self) -> Option<Vec<bool>> {
+        self.normalize_with(&EvalConfig::default())
+            .ok()?
+            .to_vec()?
+            .iter()
+            .map(|e| e.to_bool())
+            .collect()
+    }
+}
+
+pub fn t() -> Expr {
+    Expr::lambda("x", Expr::lambda("y", Expr::var("x")))
+}
+
+pub fn f() -> Expr {
+    Expr::lambda("x", Expr::lambda("y", Expr::var("y")))
+}
+
+pub fn and() -> Expr {
+    Expr::Lam(
+        "x".to_string(),
+        Box::new(Expr::Lam(
+            "y".to_string(),
+            Box::new(
+                Expr::Var("x".to_string())
+                    .apply(&Expr::Var("y".to_string()))
+                    .apply(&f()),
+            ),
+        )),
+    )
+}
+
+pub fn or() -> Expr {
+    Expr::Lam(
+        "x".to_string(),
+        Box::new(Expr::Lam(
+            "y".to_string(),
+            Box::new(
+                Expr::Var("x".to_string())
+                    .apply(&t())
+                    .apply(&Expr::Var("y".to_string())),
+            ),
+        )),
+    )
+}
+
+pub fn not() -> Expr {
+    Expr::Lam(
+        "x".to_string(),
+        Box::new(
+            Expr::Var("x".to_string())
+                .apply(&f())
+                .apply(&t())
+                .reduction(),
+        ),
+    )
+}
+
+pub fn ite() -> Expr {
+    Expr::lambda(
+        "c",
+        Expr::lambda(
+            "l",
+            Expr::lambda(
+                "r",
+                Expr::var("c").apply(&Expr::var("l")).apply(&Expr::var("r")),
+            ),
+        ),
+    )
+}
+
+// Applies `op` to every combination of `arity` booleans, in counting
+// order from all-false to all-true, and decodes each result. `None` marks
+// a result that is not a boolean or did not normalize in `max_steps`.
+// Panics when the 2^`arity` rows do not fit in a `usize`, i.e. when `arity`
+// is `usize::BITS` or more; any arity near that is far too large to build
+// anyway.
+pub fn boolean_truth_table(
+    op: &Expr,
+    arity: usize,
+    max_steps: usize,
+) -> Vec<(Vec<bool>, Option<bool>)> {
+    let rows = u32::try_from(arity)
+        .ok()
+        .and_then(|arity| 1usize.checked_shl(arity))
+        .unwrap_or_else(|| panic!("a truth table of arity {arity} has too many rows"));
+    (0..rows)
+        .map(|row| {
+            let inputs: Vec<bool> = (0..arity)
+                .map(|i| row & (1 << (arity - 1 - i)) != 0)
+                .collect();
+            let applied = inputs
+                .iter()
+                .fold(op.clone(), |acc, &b| acc.apply(&if b { t() } else { f() }));
+            let output = applied
+                .full_reduction_bounded(max_steps)
+                .and_then(|e| e.to_bool());
+            (inputs, output)
+        })
+        .collect()
+}
+
+pub mod sum {
+    use super::*;
+
+    pub fn left() -> Expr {
+        // λx l r. l x
+        Expr::lambda(
+            "x",
+            Expr::lambda(
+                "l",
+                Expr::lambda("r", Expr::var("l").apply(&Expr::var("x"))),
+            ),
+        )
+    }
+
+    pub fn right() -> Expr {
+        // λx l r. r x
+        Expr::lambda(
+            "x",
+            Expr::lambda(
+                "l",
+                Expr::lambda("r", Expr::var("r").apply(&Expr::var("x"))),
+            ),
+        )
+    }
+
+    pub fn case() -> Expr {
+        // λs f g. s f g
+        Expr::lambda(
+            "s",
+            Expr::lambda(
+                "f",
+                Expr::lambda(
+                    "g",
+                    Expr::var("s").apply(&Expr::var("f")).apply(&Expr::var("g")),
+                ),
+            ),
+        )
+    }
+}
+
+pub trait ChurchNumeral {
+    fn to_church(&self) -> Expr;
+}
+
+// `n.to_church()` is a term of size n, so stick to small values: even
+// `u16::MAX` builds a 65535-deep application.
+macro_rules! church_numeral {
+    ($($t:ty),*) => {$(
+        impl ChurchNumeral for $t {
+            fn to_church(&self) -> Expr {
+                let inside = (0..*self).fold(Expr::var("x"), |acc, _| {
+                    Expr::App(Box::new(Expr::var("f")), Box::new(acc))
+                });
+                Expr::lambda("f", Expr::lambda("x", inside))
+            }
+        }
+    )*};
+}
+
+church_numeral!(u8, u16, u32, u64, usize);
+
+// Rust values with a standard lambda encoding: booleans as `t`/`f`,
+// numbers as Church numerals, and terms as themselves.
+pub trait ToLambda {
+    fn to_lambda(&self) -> Expr;
+}
+
+impl ToLambda for Expr {
+    fn to_lambda(&self) -> Expr {
+        self.clone()
+    }
+}
+
+impl ToLambda for bool {
+    fn to_lambda(&self) -> Expr {
+        if *self {
+            t()
+        } else {
+            f()
+        }
+    }
+}
+
+impl ToLambda for u32 {
+    fn to_lambda(&self) -> Expr {
+        self.to_church()
+    }
+}
+
+// Lets mixed argument lists be written as `[&true as &dyn ToLambda, &1u32]`.
+impl<T: ToLambda + ?Sized> ToLambda for &T {
+    fn to_lambda(&self) -> Expr {
+        (**self).to_lambda()
+    }
+}
+
+// Applies `head` to the encodings of `args`, left to right.
+pub fn applied<I: IntoIterator<Item = impl ToLambda>>(head: Expr, args: I) -> Expr {
+    args.into_iter()
+        .fold(head, |acc, arg| acc.apply(&arg.to_lambda()))
+}
+
+// `n.to_church()`, for call sites that read better as a function.
+pub fn nat(n: u32) -> Expr {
+    n.to_church()
+}
+
+// A term that reduces to the numeral `n` but is built by doubling, as
+// `succ (mul 2 (... (mul 2 1)))` following the binary digits of `n`, so
+// its size and depth grow with the number of digits rather than with `n`.
+// It is not a numeral until reduced; under `NumeralAware` that takes one
+// step per `mul` and `succ`, 14 for `1000`.
+pub fn church_fast(n: u32) -> Expr {
+    if n == 0 {
+        return nat(0);
+    }
+    let digits = u32::BITS - n.leading_zeros();
+    (0..digits - 1).rev().fold(nat(1), |acc, i| {
+        let doubled = mul().apply(&nat(2)).apply(&acc);
+        if n >> i & 1 == 1 {
+            succ().apply(&doubled)
+        } else {
+            doubled
+        }
+    })
+}
+
+// `add a b`, except that for two literal numerals the sum is computed in
+// Rust and encoded directly, so nothing is left to reduce. A term that is a
+// numeral only after reduction gets the application.
+pub fn church_add(a: &Expr, b: &Expr) -> Expr {
+    literal_or_apply(a, b, u32::checked_add, add)
+}
+
+// `church_add` for `mul`.
+pub fn church_mul(a: &Expr, b: &Expr) -> Expr {
+    literal_or_apply(a, b, u32::checked_mul, mul)
+}
+
+fn literal_or_apply(
+    a: &Expr,
+    b: &Expr,
+    op: fn(u32, u32) -> Option<u32>,
+    term: fn() -> Expr,
+) -> Expr {
+    match (a.numeral_spine(), b.numeral_spine()) {
+        (Some(m), Some(n)) => match op(m, n) {
+            Some(value) => value.to_church(),
+            None => term().apply(a).apply(b),
+        },
+        _ => term().apply(a).apply(b),
+    }
+}
+
+// A numeral to build arithmetic from with Rust operators: `Church(2) +
+// Church(3)` is the term `add 2 3`, and `[1, 2, 3].map(Church)` summed or
+// multiplied folds `add` or `mul` over them from the left. Nothing is
+// reduced; use `reduce_to_numeral` on the result.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Church(pub u32);
+
+// An unreduced arithmetic term, what the operators on `Church` build.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChurchExpr(pub Expr);
+
+impl From<Church> for ChurchExpr {
+    fn from(n: Church) -> ChurchExpr {
+        ChurchExpr(n.0.to_church())
+    }
+}
+
+impl ToLambda for Church {
+    fn to_lambda(&self) -> Expr {
+        self.0.to_church()
+    }
+}
+
+impl ToLambda for ChurchExpr {
+    fn to_lambda(&self) -> Expr {
+        self.0.clone()
+    }
+}
+
+macro_rules! church_op {
+    ($trait:ident, $method:ident, $term:ident) => {
+        impl<T: Into<ChurchExpr>> core::ops::$trait<T> for ChurchExpr {
+            type Output = ChurchExpr;
+
+            fn $method(self, other: T) -> ChurchExpr {
+                ChurchExpr($term().apply(&self.0).apply(&other.into().0))
+            }
+        }
+
+        impl<T: Into<ChurchExpr>> core::ops::$trait<T> for Church {
+            type Output = ChurchExpr;
+
+            fn $method(self, other: T) -> ChurchExpr {
+                ChurchExpr::from(self).$method(other)
+            }
+        }
+    };
+}
+
+church_op!(Add, add, add);
+church_op!(Mul, mul, mul);
+
+// The empty sum is `0` and the empty product `1`.
+impl<T: Into<ChurchExpr>> core::iter::Sum<T> for ChurchExpr {
+    fn sum<I: Iterator<Item = T>>(iter: I) -> ChurchExpr {
+        use core::ops::Add;
+        iter.map(Into::into)
+            .reduce(ChurchExpr::add)
+            .unwrap_or(Church(0).into())
+    }
+}
+
+impl<T: Into<ChurchExpr>> core::iter::Product<T> for ChurchExpr {
+    fn product<I: Iterator<Item = T>>(iter: I) -> ChurchExpr {
+        use core::ops::Mul;
+        iter.map(Into::into)
+            .reduce(ChurchExpr::mul)
+            .unwrap_or(Church(1).into())
+    }
+}
+
+pub fn succ() -> Expr {
+    Expr::lambda(
+        "n",
+        Expr::lambda(
+            "f",
+            Expr::lambda(
+                "x",
+                Expr::var("f").apply(&Expr::var("n").apply(&Expr::var("f")).apply(&Expr::var("x"))),
+            ),
+        ),
+    )
+}
+
+pub fn add() -> Expr {
+    // λnmf x.nf (mf x)
+    Expr::lambda(
+        "n",
+        Expr::lambda(
+            "m",
+            Expr::lambda(
+                "f",
+                Expr::lambda(
+                    "x",
+                    Expr::var("n")
+                        .apply(&Expr::var("f"))
+                        .apply(&Expr::var("m").apply(&Expr::var("f")).apply(&Expr::var("x"))),
+                ),
+            ),
+        ),
+    )
+}
+
+pub fn mul() -> Expr {
+    // λnmf.n(mf)
+    Expr::lambda(
+        "n",
+        Expr::lambda(
+            "m",
+            Expr::lambda(
+                "f",
+                Expr::var("n").apply(&Expr::var("m").apply(&Expr::var("f"))),
+            ),
+        ),
+    )
+}
+
+pub fn exp() -> Expr {
+    // λb e. e b
+    Expr::lambda(
+        "b",
+        Expr::lambda("e", Expr::var("e").apply(&Expr::var("b"))),
+    )
+}
+
+pub fn is_zero() -> Expr {
+    // λn. n (λx. F) T
+    Expr::lambda(
+        "n",
+        Expr::var("n").apply(&Expr::lambda("x", f())).apply(&t()),
+    )
+}
+
+pub fn pred() -> Expr {
+    // λn f x. n (λg h. h (g f)) (λu. x) (λu. u)
+    Expr::lambda(
+        "n",
+        Expr::lambda(
+            "f",
+            Expr::lambda(
+                "x",
+                Expr::var("n")
+                    .apply(&Expr::lambda(
+                        "g",
+                        Expr::lambda(
+                            "h",
+                            Expr::var("h").apply(&Expr::var("g").apply(&Expr::var("f"))),
+                        ),
+                    ))
+                    .apply(&Expr::lambda("u", Expr::var("x")))
+                    .apply(&Expr::lambda("u", Expr::var("u"))),
+            ),
+        ),
+    )
+}
+
+pub fn sub() -> Expr {
+    // λm n. n pred m
+    Expr::lambda(
+        "m",
+        Expr::lambda("n", Expr::var("n").apply(&pred()).apply(&Expr::var("m"))),
+    )
+}
+
+pub fn leq() -> Expr {
+    // λm n. is_zero (sub m n)
+    Expr::lambda(
+        "m",
+        Expr::lambda(
+            "n",
+            is_zero().apply(&sub().apply(&Expr::var("m")).apply(&Expr::var("n"))),
+        ),
+    )
+}
+
+pub fn lt() -> Expr {
+    // λm n. not (leq n m)
+    Expr::lambda(
+        "m",
+        Expr::lambda(
+            "n",
+            not().apply(&leq().apply(&Expr::var("n")).apply(&Expr::var("m"))),
+        ),
+    )
+}
+
+pub fn eq_nat() -> Expr {
+    // λm n. and (leq m n) (leq n m)
+    Expr::lambda(
+        "m",
+        Expr::lambda(
+            "n",
+            and()
+                .apply(&leq().apply(&Expr::var("m")).apply(&Expr::var("n")))
+                .apply(&leq().apply(&Expr::var("n")).apply(&Expr::var("m"))),
+        ),
+    )
+}
+
+// Division and remainder by repeated subtraction, tied through `Z`: while
+// `n ≤ m`, subtract `n` from `m`. Both loop forever when `n` is 0. Slow: in
+// normal order `m` is a growing tower of unevaluated `sub`s that every `leq`
+// recomputes.
+pub fn div() -> Expr {
+    // Z (λd m n. leq n m (succ (d (sub m n) n)) 0)
+    let m = Expr::var("m");
+    let n = Expr::var("n");
+    let rest = sub().apply(&m).apply(&n);
+    combinators::z().apply(&Expr::lambda(
+        "d",
+        Expr::lambda(
+            "m",
+            Expr::lambda(
+                "n",
+                leq()
+                    .apply(&n)
+                    .apply(&m)
+                    .apply(&succ().apply(&Expr::var("d").apply(&rest).apply(&n)))
+                    .apply(&Expr::church_zero()),
+            ),
+        ),
+    ))
+}
+
+pub fn mod_() -> Expr {
+    // Z (λr m n. leq n m (r (sub m n) n) m)
+    let m = Expr::var("m");
+    let n = Expr::var("n");
+    let rest = sub().apply(&m).apply(&n);
+    combinators::z().apply(&Expr::lambda(
+        "r",
+        Expr::lambda(
+            "m",
+            Expr::lambda(
+                "n",
+                leq()
+                    .apply(&n)
+                    .apply(&m)
+                    .apply(&Expr::var("r").apply(&rest).apply(&n))
+                    .apply(&m),
+            ),
+        ),
+    ))
+}
+
+pub fn tuple(first: &Expr, second: &Expr) -> Expr {
+    Expr::lambda("f", Expr::var("f").apply(first).apply(second))
+}
+
+pub fn first() -> Expr {
+    Expr::lambda("p", Expr::var("p").apply(&t()))
+}
+
+pub fn second() -> Expr {
+    Expr::lambda("p", Expr::var("p").apply(&f()))
+}
+
+pub fn nil() -> Expr {
+    Expr::lambda("c", Expr::lambda("n", Expr::var("n")))
+}
+
+pub fn cons() -> Expr {
+    // λh t c n. c h (t c n)
+    Expr::lambda(
+        "h",
+        Expr::lambda(
+            "t",
+            Expr::lambda(
+                "c",
+                Expr::lambda(
+                    "n",
+                    Expr::var("c")
+                        .apply(&Expr::var("h"))
+                        .apply(&Expr::var("t").apply(&Expr::var("c")).apply(&Expr::var("n"))),
+                ),
+            ),
+        ),
+    )
+}
+
+// A list is its own right fold, so these all work by applying the list to a
+// new `cons` and `nil`.
+pub fn foldr() -> Expr {
+    // λf z xs. xs f z
+    Expr::lambda(
+        "f",
+        Expr::lambda(
+            "z",
+            Expr::lambda(
+                "xs",
+                Expr::var("xs")
+                    .apply(&Expr::var("f"))
+                    .apply(&Expr::var("z")),
+            ),
+        ),
+    )
+}
+
+pub fn map() -> Expr {
+    // λf xs c n. xs (λh t. c (f h) t) n
+    let cell = Expr::lambda(
+        "h",
+        Expr::lambda(
+            "t",
+            Expr::var("c")
+                .apply(&Expr::var("f").apply(&Expr::var("h")))
+                .apply(&Expr::var("t")),
+        ),
+    );
+    Expr::lambda(
+        "f",
+        Expr::lambda(
+            "xs",
+            Expr::lambda(
+                "c",
+                Expr::lambda("n", Expr::var("xs").apply(&cell).apply(&Expr::var("n"))),
+            ),
+        ),
+    )
+}
+
+pub fn filter() -> Expr {
+    // λp xs c n. xs (λh t. p h (c h t) t) n
+    let cell = Expr::lambda(
+        "h",
+        Expr::lambda(
+            "t",
+            Expr::var("p")
+                .apply(&Expr::var("h"))
+                .apply(&Expr::var("c").apply(&Expr::var("h")).apply(&Expr::var("t")))
+                .apply(&Expr::var("t")),
+        ),
+    );
+    Expr::lambda(
+        "p",
+        Expr::lambda(
+            "xs",
+            Expr::lambda(
+                "c",
+                Expr::lambda("n", Expr::var("xs").apply(&cell).apply(&Expr::var("n"))),
+            ),
+        ),
+    )
+}
+
+pub fn append() -> Expr {
+    // λxs ys c n. xs c (ys c n)
+    Expr::lambda(
+        "xs",
+        Expr::lambda(
+            "ys",
+            Expr::lambda(
+                "c",
+                Expr::lambda(
+                    "n",
+                    Expr::var("xs").apply(&Expr::var("c")).apply(
+                        &Expr::var("ys")
+                            .apply(&Expr::var("c"))
+                            .apply(&Expr::var("n")),
+                    ),
+                ),
+            ),
+        ),
+    )
+}
+
+pub fn length() -> Expr {
+    // λxs f x. xs (λh t. f t) x
+    Expr::lambda(
+        "xs",
+        Expr::lambda(
+            "f",
+            Expr::lambda(
+                "x",
+                Expr::var("xs")
+                    .apply(&Expr::lambda(
+                        "h",
+                        Expr::lambda("t", Expr::var("f").apply(&Expr::var("t"))),
+                    ))
+                    .apply(&Expr::var("x")),
+            ),
+        ),
+    )
+}
+
+// The standard combinators, named as in combinatory logic.
+pub mod combinators {
+    use super::*;
+
+    pub fn s() -> Expr {
+        // λx y z. x z (y z)
+        Expr::lambda(
+            "x",
+            Expr::lambda(
+                "y",
+                Expr::lambda(
+                    "z",
+                    Expr::var("x")
+                        .apply(&Expr::var("z"))
+                        .apply(&Expr::var("y").apply(&Expr::var("z"))),
+                ),
+            ),
+        )
+    }
+
+    pub fn k() -> Expr {
+        // λx y. x
+        Expr::lambda("x", Expr::lambda("y", Expr::var("x")))
+    }
+
+    pub fn i() -> Expr {
+        // λx. x
+        Expr::lambda("x", Expr::var("x"))
+    }
+
+    pub fn b() -> Expr {
+        // λf g x. f (g x)
+        Expr::lambda(
+            "f",
+            Expr::lambda(
+                "g",
+                Expr::lambda(
+                    "x",
+                    Expr::var("f").apply(&Expr::var("g").apply(&Expr::var("x"))),
+                ),
+            ),
+        )
+    }
+
+    pub fn c() -> Expr {
+        // λf x y. f y x
+        Expr::lambda(
+            "f",
+            Expr::lambda(
+                "x",
+                Expr::lambda(
+                    "y",
+                    Expr::var("f").apply(&Expr::var("y")).apply(&Expr::var("x")),
+                ),
+            ),
+        )
+    }
+
+    pub fn w() -> Expr {
+        // λf x. f x x
+        Expr::lambda(
+            "f",
+            Expr::lambda(
+                "x",
+                Expr::var("f").apply(&Expr::var("x")).apply(&Expr::var("x")),
+            ),
+        )
+    }
+
+    pub fn y() -> Expr {
+        // λf. (λx. f (x x)) (λx. f (x x))
+        let half = Expr::lambda(
+            "x",
+            Expr::var("f").apply(&Expr::var("x").apply(&Expr::var("x"))),
+        );
+        Expr::lambda("f", half.apply(&half))
+    }
+
+    pub fn z() -> Expr {
+        // λf. (λx. f (λv. x x v)) (λx. f (λv. x x v)), `Y` with the
+        // self-application eta-expanded so it also works under call by value
+        let half = Expr::lambda(
+            "x",
+            Expr::var("f").apply(&Expr::lambda(
+                "v",
+                Expr::var("x").apply(&Expr::var("x")).apply(&Expr::var("v")),
+            )),
+        );
+        Expr::lambda("f", half.apply(&half))
+    }
+}
+
+#[cfg(feature = "std")]
+type Registry = HashMap<&'static str, fn() -> Expr>;
+
+#[cfg(feature = "std")]
+fn builtins() -> &'static Registry {
+    static BUILTINS: std::sync::OnceLock<Registry> = std::sync::OnceLock::new();
+    BUILTINS.get_or_init(|| {
+        HashMap::from([
+            ("S", combinators::s as fn() -> Expr),
+            ("K", combinators::k),
+            ("I", combinators::i),
+            ("B", combinators::b),
+            ("C", combinators::c),
+            ("W", combinators::w),
+            ("Y", combinators::y),
+            ("Z", combinators::z),
+            ("true", t),
+            ("false", f),
+            ("and", and),
+            ("or", or),
+            ("not", not),
+            ("ite", ite),
+            ("left", sum::left),
+            ("right", sum::right),
+            ("case", sum::case),
+            ("succ", succ),
+            ("add", add),
+            ("mul", mul),
+            ("exp", exp),
+            ("is_zero", is_zero),
+            ("pred", pred),
+            ("sub", sub),
+            ("leq", leq),
+            ("lt", lt),
+            ("eq_nat", eq_nat),
+            ("div", div),
+            ("mod", mod_),
+            ("first", first),
+            ("second", second),
+            ("nil", nil),
+            ("cons", cons),
+            ("foldr", foldr),
+            ("map", map),
+            ("filter", filter),
+            ("append", append),
+            ("length", length),
+        ])
+    })
+}
+
+// Looks up a library term by name: the combinators by their capital
+// letter, everything else by its function name here.
+#[cfg(feature = "std")]
+pub fn builtin(name: &str) -> Option<Expr> {
+    builtins().get(name).map(|make| make())
+}
+
+// Every name `builtin` knows, sorted.
+#[cfg(feature = "std")]
+pub fn builtin_names() -> Vec<&'static str> {
+    let mut names: Vec<_> = builtins().keys().copied().collect();
+    names.sort();
+    names
+}
@@ -1,356 +1,22 @@
-#![feature(box_patterns)]
-use std::sync::atomic::{AtomicU32, Ordering};
-
-static COUNTER: AtomicU32 = AtomicU32::new(0);
-
-fn fresh() -> String {
-    COUNTER.fetch_add(1, Ordering::SeqCst);
-    let c = COUNTER.load(Ordering::SeqCst) as u32;
-    format!("v{c}")
-}
-
-pub type Id = String;
-
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub enum Expr {
-    Lam(Id, Box<Expr>),
-    App(Box<Expr>, Box<Expr>),
-    Var(Id),
-}
-
-#[derive(PartialEq, Eq)]
-pub enum DeBrujin {
-    Lam(Box<DeBrujin>),
-    App(Box<DeBrujin>, Box<DeBrujin>),
-    Var(u32),
-}
-
-impl ToString for Expr {
-    fn to_string(&self) -> String {
-        match self {
-            Expr::Lam(id, expr) => format!("(λ{}. {})", id, expr.to_string()),
-            Expr::App(expr1, expr2) => format!("({} {})", expr1.to_string(), expr2.to_string()),
-            Expr::Var(id) => id.clone(),
-        }
-    }
-}
-
-impl ToString for DeBrujin {
-    fn to_string(&self) -> String {
-        match self {
-            DeBrujin::Lam(expr) => format!("(λ. {})", expr.to_string()),
-            DeBrujin::App(expr1, expr2) => format!("({} {})", expr1.to_string(), expr2.to_string()),
-            DeBrujin::Var(id) => format!("{}", id),
-        }
-    }
-}
-
-impl Expr {
-    fn fv(&self) -> Vec<Id> {
-        match self {
-            Expr::Lam(id, expr) => {
-                let mut fv = expr.fv();
-                fv.retain(|x| x != id);
-                fv
-            }
-            Expr::App(expr1, expr2) => {
-                let mut fv1 = expr1.fv();
-                let mut fv2 = expr2.fv();
-                fv1.append(&mut fv2);
-                fv1
-            }
-            Expr::Var(id) => vec![id.clone()],
-        }
-    }
-
-    fn debrujin(&self) -> DeBrujin {
-        self.debrujin_with(&vec![])
-    }
-
-    fn debrujin_with(&self, ctx: &Vec<Id>) -> DeBrujin {
-        match self {
-            Expr::Lam(id, expr) => {
-                let mut ctx = ctx.clone();
-                ctx.push(id.clone());
-                DeBrujin::Lam(Box::new(expr.debrujin_with(&ctx)))
-            }
-            Expr::App(expr1, expr2) => DeBrujin::App(
-                Box::new(expr1.debrujin_with(ctx)),
-                Box::new(expr2.debrujin_with(ctx)),
-            ),
-            Expr::Var(id) => {
-                if let Some(pos) = ctx.iter().position(|x| x == id) {
-                    DeBrujin::Var(pos as u32)
-                } else {
-                    panic!("Unbound variable {}", id)
-                }
-            }
-        }
-    }
-
-    fn equivalence(&self, other: &Expr) -> bool {
-        self.full_reduction().debrujin() == other.full_reduction().debrujin()
-    }
-
-    fn exact_equivalence(&self, other: &Expr) -> bool {
-        self.debrujin() == other.debrujin()
-    }
-
-    fn substitution(&self, _id: &String, e: &Expr) -> Expr {
-        match self {
-            Expr::Lam(id, expr) => {
-                if id == _id {
-                    self.clone()
-                } else {
-                    let fv = e.fv();
-                    if !fv.contains(id) {
-                        Expr::Lam(id.clone(), Box::new(expr.substitution(_id, e)))
-                    } else {
-                        let nid = fresh();
-                        Expr::Lam(
-                            nid.clone(),
-                            Box::new(expr.substitution(id, &Expr::Var(nid)).substitution(id, e)),
-                        )
-                    }
-                }
-            }
-            Expr::App(e1, e2) => Expr::App(
-                Box::new(e1.substitution(_id, e)),
-                Box::new(e2.substitution(_id, e)),
-            ),
-            Expr::Var(id) => {
-                if _id == id {
-                    e.clone()
-                } else {
-                    self.clone()
-                }
-            }
-        }
-    }
-
-    fn reduction(&self) -> Expr {
-        match self {
-            Expr::Lam(id, expr) => Expr::Lam(id.clone(), Box::new(expr.reduction())),
-            Expr::App(m, n) => {
-                let mr = m.reduction();
-                let nr = n.reduction();
-                if let Expr::Lam(id, mr) = mr {
-                    mr.substitution(&id, &nr)
-                } else {
-                    Expr::App(Box::new(mr), Box::new(nr))
-                }
-            }
-            Expr::Var(_) => self.clone(),
-        }
-    }
-
-    fn full_reduction(&self) -> Expr {
-        let mut expr = self.clone();
-        let mut expr2 = expr.reduction();
-        while !expr.exact_equivalence(&expr2) {
-            expr = expr2;
-            expr2 = expr.reduction();
-        }
-        expr
-    }
-
-    pub fn apply(&self, other: &Expr) -> Expr {
-        Expr::App(Box::new(self.clone()), Box::new(other.clone()))
-    }
-
-    pub fn lambda(id: &str, expr: Expr) -> Expr {
-        Expr::Lam(id.to_string(), Box::new(expr))
-    }
-
-    pub fn var(id: &str) -> Expr {
-        Expr::Var(id.to_string())
-    }
-
-    fn to_app_vec(&self) -> Vec<Expr> {
-        match self {
-            Expr::App(m, n) => {
-                let mut m = m.to_app_vec();
-                let mut n = n.to_app_vec();
-                m.append(&mut n);
-                m
-            }
-            _ => vec![self.clone()],
-        }
-    }
-
-    pub fn to_numeral(&self) -> u32 {
-        match self {
-            Expr::Lam(f, box Expr::Lam(x, apps)) => {
-                if f == "f" && x == "x" {
-                    let appvec = apps.to_app_vec();
-                    let mut count = 0;
-                    if appvec.last().unwrap() == &Expr::Var("x".to_string()) {
-                        for app in appvec.split_last().unwrap().1 {
-                            if *app == Expr::Var("f".to_string()) {
-                                count += 1;
-                            } else {
-                                panic!("Not a numeral")
-                            }
-                        }
-                        count
-                    } else {
-                        panic!("Not a numeral")
-                    }
-                } else {
-                    panic!("Not a numeral")
-                }
-            }
-            _ => panic!("Not a numeral"),
-        }
+use lcrs::stlc::{Type, TypeCtx};
+use lcrs::terms::{self, ChurchNumeral};
+use lcrs::{
+    assert_reduces_to, integers, scott, Dir, Env, Expr, FreshSupply, Notation, PrintStyle, Strategy,
+};
+
+// Prints an `equivalence` outcome, which is `None` when a side runs out of
+// steps.
+fn verdict(outcome: Option<bool>) -> &'static str {
+    match outcome {
+        Some(true) => "true",
+        Some(false) => "false",
+        None => "inconclusive",
     }
 }
 
-mod lcterms {
-    use super::*;
-    pub fn t() -> Expr {
-        Expr::lambda("x", Expr::lambda("y", Expr::var("x")))
-    }
-
-    pub fn f() -> Expr {
-        Expr::lambda("x", Expr::lambda("y", Expr::var("y")))
-    }
-
-    pub fn and() -> Expr {
-        Expr::Lam(
-            "x".to_string(),
-            Box::new(Expr::Lam(
-                "y".to_string(),
-                Box::new(
-                    Expr::Var("x".to_string())
-                        .apply(&Expr::Var("y".to_string()))
-                        .apply(&f()),
-                ),
-            )),
-        )
-    }
-
-    pub fn or() -> Expr {
-        Expr::Lam(
-            "x".to_string(),
-            Box::new(Expr::Lam(
-                "y".to_string(),
-                Box::new(
-                    Expr::Var("x".to_string())
-                        .apply(&t())
-                        .apply(&Expr::Var("y".to_string())),
-                ),
-            )),
-        )
-    }
-
-    pub fn not() -> Expr {
-        Expr::Lam(
-            "x".to_string(),
-            Box::new(
-                Expr::Var("x".to_string())
-                    .apply(&f())
-                    .apply(&t())
-                    .reduction(),
-            ),
-        )
-    }
-
-    pub fn ite() -> Expr {
-        Expr::lambda(
-            "c",
-            Expr::lambda(
-                "l",
-                Expr::lambda(
-                    "r",
-                    Expr::var("c").apply(&Expr::var("l")).apply(&Expr::var("r")),
-                ),
-            ),
-        )
-    }
-
-    pub trait ChurchNumeral {
-        fn to_church(&self) -> Expr;
-    }
-
-    impl ChurchNumeral for u32 {
-        fn to_church(&self) -> Expr {
-            let fx = Expr::Var("x".to_string());
-
-            let inside = (0..*self).fold(fx, |acc, _| Expr::Var("f".to_string()).apply(&acc));
-
-            let expr = Expr::Lam(
-                "f".to_string(),
-                Box::new(Expr::Lam("x".to_string(), Box::new(inside))),
-            );
-
-            expr
-        }
-    }
-
-    pub fn succ() -> Expr {
-        Expr::lambda(
-            "n",
-            Expr::lambda(
-                "f",
-                Expr::lambda(
-                    "x",
-                    Expr::var("f")
-                        .apply(&Expr::var("n").apply(&Expr::var("f")).apply(&Expr::var("x"))),
-                ),
-            ),
-        )
-    }
-
-    pub fn add() -> Expr {
-        // λnmf x.nf (mf x)
-        Expr::lambda(
-            "n",
-            Expr::lambda(
-                "m",
-                Expr::lambda(
-                    "f",
-                    Expr::lambda(
-                        "x",
-                        Expr::var("n")
-                            .apply(&Expr::var("f"))
-                            .apply(&Expr::var("m").apply(&Expr::var("f")).apply(&Expr::var("x"))),
-                    ),
-                ),
-            ),
-        )
-    }
-
-    pub fn mul() -> Expr {
-        // λnmf.n(mf)
-        Expr::lambda(
-            "n",
-            Expr::lambda(
-                "m",
-                Expr::lambda(
-                    "f",
-                    Expr::var("n").apply(&Expr::var("m").apply(&Expr::var("f"))),
-                ),
-            ),
-        )
-    }
-
-    pub fn tuple(first: &Expr, second: &Expr) -> Expr {
-        Expr::lambda("f", Expr::var("f").apply(first).apply(second))
-    }
-
-    pub fn first() -> Expr {
-        Expr::lambda("p", Expr::var("p").apply(&t()))
-    }
-
-    pub fn second() -> Expr {
-        Expr::lambda("p", Expr::var("p").apply(&f()))
-    }
-}
-
-use lcterms::ChurchNumeral;
 fn main() {
     let expr = Expr::Lam("x".to_string(), Box::new(Expr::Var("x".to_string())));
-    println!("{}", expr.to_string());
+    println!("{}", expr);
 
     let expr = Expr::App(
         Box::new(Expr::Lam(
@@ -360,7 +26,7 @@ fn main() {
         Box::new(Expr::Var("y".to_string())),
     );
 
-    println!("{}", expr.to_string());
+    println!("{}", expr);
     println!("{:?}", expr.fv());
 
     let expr = Expr::App(
@@ -374,144 +40,838 @@ fn main() {
         )),
     );
 
-    println!("{}", expr.to_string());
-    println!("{}", expr.debrujin().to_string());
+    println!("{}", expr);
+    println!("{}", expr.debrujin());
     println!("{:?}", expr.fv());
-    println!("{}", expr.reduction().to_string());
-    println!("{}", lcterms::t().debrujin().to_string());
-    println!("{}", lcterms::f().debrujin().to_string());
-    println!("{}", lcterms::and().debrujin().to_string());
-    let tt = lcterms::and()
-        .apply(&lcterms::t())
-        .apply(&lcterms::t())
+    println!("{}", expr.reduction());
+    println!("{}", terms::t().debrujin());
+    println!("{}", terms::f().debrujin());
+    println!("{}", terms::and().debrujin());
+    let tt = terms::and()
+        .apply(&terms::t())
+        .apply(&terms::t())
         .reduction();
-    println!("{}", tt.equivalence(&lcterms::t()));
+    println!("{}", verdict(tt.equivalence(&terms::t())));
 
-    let tf = lcterms::and()
-        .apply(&lcterms::t())
-        .apply(&lcterms::f())
+    let tf = terms::and()
+        .apply(&terms::t())
+        .apply(&terms::f())
         .reduction();
 
-    println!("{}", tf.equivalence(&lcterms::f()));
+    println!("{}", verdict(tf.equivalence(&terms::f())));
 
-    let ft = lcterms::and()
-        .apply(&lcterms::f())
-        .apply(&lcterms::t())
+    let ft = terms::and()
+        .apply(&terms::f())
+        .apply(&terms::t())
         .reduction();
 
-    println!("{}", ft.equivalence(&lcterms::f()));
+    println!("{}", verdict(ft.equivalence(&terms::f())));
 
-    let ff = lcterms::and()
-        .apply(&lcterms::f())
-        .apply(&lcterms::f())
+    let ff = terms::and()
+        .apply(&terms::f())
+        .apply(&terms::f())
         .reduction();
 
-    println!("{}", ff.equivalence(&lcterms::f()));
+    println!("{}", verdict(ff.equivalence(&terms::f())));
 
-    println!("{}", lcterms::or().debrujin().to_string());
-    let tt = lcterms::or()
-        .apply(&lcterms::t())
-        .apply(&lcterms::t())
+    println!("{}", terms::or().debrujin());
+    let tt = terms::or()
+        .apply(&terms::t())
+        .apply(&terms::t())
         .reduction();
 
-    println!("{}", tt.equivalence(&lcterms::t()));
+    println!("{}", verdict(tt.equivalence(&terms::t())));
 
-    let tf = lcterms::or()
-        .apply(&lcterms::t())
-        .apply(&lcterms::f())
+    let tf = terms::or()
+        .apply(&terms::t())
+        .apply(&terms::f())
         .reduction();
 
-    println!("{}", tf.equivalence(&lcterms::t()));
+    println!("{}", verdict(tf.equivalence(&terms::t())));
 
-    let ft = lcterms::or()
-        .apply(&lcterms::f())
-        .apply(&lcterms::t())
+    let ft = terms::or()
+        .apply(&terms::f())
+        .apply(&terms::t())
         .reduction();
 
-    println!("{}", ft.equivalence(&lcterms::t()));
+    println!("{}", verdict(ft.equivalence(&terms::t())));
 
-    let ff = lcterms::or()
-        .apply(&lcterms::f())
-        .apply(&lcterms::f())
+    let ff = terms::or()
+        .apply(&terms::f())
+        .apply(&terms::f())
         .reduction();
 
-    println!("{}", ff.equivalence(&lcterms::f()));
+    println!("{}", verdict(ff.equivalence(&terms::f())));
 
-    println!("{}", lcterms::not().debrujin().to_string());
+    println!("{}", terms::not().debrujin());
 
-    let t = lcterms::not().apply(&lcterms::t()).reduction();
-    println!("{}", t.equivalence(&lcterms::f()));
+    let t = terms::not().apply(&terms::t()).reduction();
+    println!("{}", verdict(t.equivalence(&terms::f())));
 
-    let f = lcterms::not().apply(&lcterms::f()).reduction();
-    println!("{}", f.equivalence(&lcterms::t()));
+    let f = terms::not().apply(&terms::f()).reduction();
+    println!("{}", verdict(f.equivalence(&terms::t())));
 
-    let zero = 0_u32.to_church();
-    println!("{}", zero.to_string());
+    let zero = Expr::church_zero();
+    println!("{}", zero);
 
-    let one = 1_u32.to_church();
-    println!("{}", one.to_string());
+    let one = Expr::church_one();
+    println!("{}", one);
 
-    let onep = lcterms::succ().apply(&zero);
-    println!("{}", onep.to_string());
-    println!("{}", onep.reduction().to_string());
-    println!("{}", onep.reduction().reduction().to_string());
-    println!("{}", onep.reduction().reduction().reduction().to_string());
-    println!("{}", onep.full_reduction().to_string());
-    println!("{}", onep.full_reduction().equivalence(&one));
+    let onep = terms::succ().apply(&zero);
+    println!("{}", onep);
+    println!("{}", onep.reduction());
+    println!("{}", onep.reduction().reduction());
+    println!("{}", onep.reduction().reduction().reduction());
+    println!("{}", onep.full_reduction());
+    println!("{}", verdict(onep.full_reduction().equivalence(&one)));
 
     let five = 5_u32.to_church();
-    println!("{}", five.to_string());
+    println!("{}", five);
     let seven = 7_u32.to_church();
-    println!("{}", seven.to_string());
+    println!("{}", seven);
 
+    println!("{}", terms::succ().apply(&five).full_reduction());
     println!(
         "{}",
-        lcterms::succ().apply(&five).full_reduction().to_string()
+        terms::succ()
+            .apply(&five)
+            .full_reduction()
+            .to_numeral()
+            .unwrap()
     );
+    println!("{}", five.full_reduction().to_numeral().unwrap());
+    println!("{}", seven.full_reduction().to_numeral().unwrap());
+
+    println!("{}", terms::add());
+    let five_plus_seven = terms::add().apply_all(&[five.clone(), seven.clone()]);
+    println!("{}", five_plus_seven);
+    println!("{}", five_plus_seven.full_reduction());
+    println!("{}", five_plus_seven.full_reduction().to_numeral().unwrap());
+
+    let two = terms::succ().apply(&terms::succ().apply(&zero));
+    let three = terms::succ().apply(&two);
+    let twotwothree = terms::mul().apply_all_owned(vec![
+        terms::mul().apply_all(&[two.clone(), two.clone()]),
+        three.clone(),
+    ]);
+
+    let twelve = 12_u32.to_church();
+    println!("{}", twelve.full_reduction());
+    println!("{}", twotwothree.full_reduction());
+
+    let tup = terms::tuple(
+        &twelve,
+        &terms::mul().apply_all(&[twelve.clone(), twotwothree.clone()]),
+    );
+    println!("{}", tup);
     println!(
         "{}",
-        &lcterms::succ().apply(&five).full_reduction().to_numeral()
+        terms::first()
+            .apply(&tup)
+            .full_reduction()
+            .to_numeral()
+            .unwrap()
     );
-    println!("{}", five.full_reduction().to_numeral());
-    println!("{}", seven.full_reduction().to_numeral());
-
-    println!("{}", lcterms::add().to_string());
-    println!("{}", lcterms::add().apply(&five).apply(&seven).to_string());
     println!(
         "{}",
-        lcterms::add()
-            .apply(&five)
-            .apply(&seven)
+        terms::second()
+            .apply(&tup)
             .full_reduction()
-            .to_string()
+            .to_numeral()
+            .unwrap()
+    );
+
+    let deep = Expr::lambda(
+        "a",
+        Expr::lambda(
+            "b",
+            Expr::lambda(
+                "c",
+                Expr::lambda(
+                    "d",
+                    Expr::var("a").apply(&Expr::var("b").apply(&Expr::var("d"))),
+                ),
+            ),
+        ),
     );
+    println!("{}", deep.max_live_binders());
+    let (picked, live) = terms::ite()
+        .apply(&terms::t())
+        .apply(&deep)
+        .apply(&zero)
+        .full_reduction_live(3)
+        .unwrap();
+    println!("{} {}", picked, live);
+    println!("{:?}", deep.full_reduction_live(2));
+
+    let bits = terms::cons().apply_all(&[
+        terms::t(),
+        terms::cons().apply_all(&[terms::f(), terms::nil()]),
+    ]);
+    println!("{:?}", bits.to_bool_vec());
+
+    let two = 2_u32.to_church();
+    let ten = 10_u32.to_church();
     println!(
         "{}",
-        lcterms::add()
-            .apply(&five)
-            .apply(&seven)
+        terms::exp()
+            .apply_all(&[two.clone(), ten.clone()])
             .full_reduction()
-            .to_numeral()
+            .exact_equivalence(&1024_u32.to_church())
     );
+    for (m, n) in [(3_u32, 5_u32), (5, 5), (5, 3)] {
+        let (m, n) = (m.to_church(), n.to_church());
+        for op in [terms::leq(), terms::lt(), terms::eq_nat()] {
+            let applied = op.apply_all(&[m.clone(), n.clone()]);
+            print!("{:?} ", applied.full_reduction().to_bool());
+        }
+        println!();
+    }
 
-    let two = lcterms::succ().apply(&lcterms::succ().apply(&zero));
-    let three = lcterms::succ().apply(&two);
-    let twotwothree = lcterms::mul()
-        .apply(&lcterms::mul().apply(&two).apply(&two))
-        .apply(&three);
+    println!(
+        "{}",
+        twotwothree
+            .normalize_shared()
+            .exact_equivalence(&twotwothree.full_reduction())
+    );
+    println!(
+        "{}",
+        terms::second()
+            .apply(&tup)
+            .normalize_shared()
+            .exact_equivalence(&144_u32.to_church())
+    );
 
-    let twelve = 12_u32.to_church();
-    println!("{}", twelve.full_reduction().to_string());
-    println!("{}", twotwothree.full_reduction().to_string());
+    let add_two = terms::add().partial_apply(&[2_u32.to_church()]);
+    println!("{}", add_two);
+    println!(
+        "{}",
+        verdict(add_two.apply(&three).equivalence(&5_u32.to_church()))
+    );
+
+    for n in [Notation::Named, Notation::DeBruijn, Notation::Both] {
+        println!("{}", terms::t().render(n));
+    }
+
+    println!(
+        "{}",
+        twotwothree
+            .normalize_nbe()
+            .exact_equivalence(&twotwothree.full_reduction())
+    );
+    println!(
+        "{}",
+        terms::exp()
+            .apply(&two)
+            .apply(&ten)
+            .normalize_nbe()
+            .exact_equivalence(&1024_u32.to_church())
+    );
+    println!("{}", terms::pred().apply(&Expr::var("n")).normalize_nbe());
+
+    let omega = Expr::lambda("x", Expr::var("x").apply(&Expr::var("x")));
+    let omega = omega.apply(&omega);
+    let lazy = terms::t().apply(&terms::t()).apply(&omega);
+    for e in [&lazy, &onep, &twotwothree, &tup] {
+        println!("{}", e.krivine_whnf().exact_equivalence(&e.whnf()));
+    }
 
-    let tup = lcterms::tuple(&twelve, &lcterms::mul().apply(&twelve).apply(&twotwothree));
-    println!("{}", tup.to_string());
+    for (inputs, output) in terms::boolean_truth_table(&terms::and(), 2, 100) {
+        println!("{:?} {:?}", inputs, output);
+    }
+    println!("{}", tup.debrujin().to_named().alpha_eq(&tup));
+    println!("{}", Expr::var("f").eta_long(1));
+    println!("{}", terms::succ().eta_long(2));
+    let id_x = Expr::lambda("x", Expr::var("x"));
+    let id_y = Expr::lambda("y", Expr::var("y"));
+    let nested = id_x.apply(&id_y.apply(&Expr::var("z")));
+    for path in nested.redex_paths() {
+        println!("{:?} {}", path, nested.beta_reduce_at(&path).unwrap());
+    }
+    println!("{:?}", nested.beta_reduce_at(&vec![Dir::AppLeft]));
+    println!(
+        "{:?}",
+        Expr::lambda("y", Expr::var("x")).would_capture(&"x".to_string(), &Expr::var("y"))
+    );
+    println!(
+        "{} {}",
+        id_x.fingerprint() == id_y.fingerprint(),
+        terms::t().fingerprint() == terms::f().fingerprint()
+    );
+    print!("{}", terms::t().to_dot());
+    print!("{}", nested.to_dot_linked());
+    let soon = std::time::Instant::now() + std::time::Duration::from_millis(10);
+    let (unfinished, done) = omega.reduce_until_deadline(soon);
+    println!("{} {}", unfinished, done);
+    let later = std::time::Instant::now() + std::time::Duration::from_secs(1);
+    println!("{:?}", onep.reduce_until_deadline(later));
+    let scott_five = scott::nat(5);
+    println!("{} {:?}", scott_five, scott_five.scott_to_u32());
+    let scott_four = scott::pred().apply(&scott_five);
+    println!(
+        "{} {:?}",
+        scott_four.redex_paths().len(),
+        scott_four.full_reduction().scott_to_u32()
+    );
+    let is_zero_scott = scott::case()
+        .apply(&scott::zero())
+        .apply(&terms::t())
+        .apply(&Expr::lambda("p", terms::f()));
+    println!("{:?}", is_zero_scott.full_reduction().to_bool());
+    let head = scott::cons()
+        .apply(&scott_five)
+        .apply(&scott::nil())
+        .apply(&scott::nil())
+        .apply(&terms::t());
+    println!("{:?}", head.full_reduction().scott_to_u32());
+    let inl = terms::sum::left().apply(&one);
+    let inr = terms::sum::right().apply(&terms::t());
+    println!("{:?}", inl.full_reduction().to_either());
+    println!("{:?}", inr.full_reduction().to_either());
+    println!(
+        "{}",
+        verdict(
+            terms::sum::case()
+                .apply(&inl)
+                .apply(&terms::succ())
+                .apply(&terms::not())
+                .equivalence(&2_u32.to_church())
+        )
+    );
+    let omega3 = Expr::lambda(
+        "x",
+        Expr::var("x").apply(&Expr::var("x")).apply(&Expr::var("x")),
+    );
+    let omega3 = omega3.apply(&omega3);
+    for e in [
+        &omega,
+        &omega.apply(&one),
+        &omega3,
+        &lazy,
+        &twotwothree,
+        &onep,
+    ] {
+        print!("{} ", e.detect_omega());
+    }
+    println!();
+    let xyz = Expr::lambda(
+        "x",
+        Expr::lambda("y", Expr::var("x").apply(&Expr::var("z"))),
+    );
+    println!(
+        "{:?} {:?} {:?}",
+        xyz.bound_variables(),
+        xyz.fv(),
+        xyz.all_variables()
+    );
+    let fa = Expr::lambda("x", Expr::var("f").apply(&Expr::var("a")));
+    let fb = Expr::lambda("x", Expr::var("f").apply(&Expr::var("b")));
+    println!("{}", fa.common_prefix(&fb));
+    let mut defs = Env::new();
+    defs.insert("two".to_string(), 2_u32.to_church());
+    defs.insert("succ".to_string(), terms::succ());
+    println!(
+        "{}",
+        terms::t().apply(&Expr::var("two")).normalize_modulo(&defs)
+    );
+    println!(
+        "{}",
+        Expr::var("succ")
+            .apply(&Expr::var("two"))
+            .normalize_modulo(&defs)
+    );
+    println!("{} {}", id_x.to_canonical(), id_y.to_canonical());
+    println!("{}", xyz.to_canonical());
+    let conditional = terms::applied(
+        terms::ite(),
+        [&true as &dyn terms::ToLambda, &1_u32, &2_u32],
+    );
+    println!("{}", verdict(conditional.equivalence(&one)));
+    println!(
+        "{}",
+        verdict(terms::add().call(&[2_u32, 3_u32]).equivalence(&five))
+    );
+    let corpus = [
+        terms::and().call(&[true, false]),
+        terms::or().call(&[false, true]),
+        terms::not().call(&[true]),
+        terms::ite().call(&[&false as &dyn terms::ToLambda, &1_u32, &2_u32]),
+        terms::succ().call(&[3_u32]),
+        terms::add().call(&[2_u32, 3_u32]),
+        terms::mul().call(&[2_u32, 3_u32]),
+        terms::exp().call(&[2_u32, 3_u32]),
+        terms::pred().call(&[3_u32]),
+        terms::sub().call(&[5_u32, 2_u32]),
+        terms::leq().call(&[2_u32, 3_u32]),
+        terms::eq_nat().call(&[2_u32, 2_u32]),
+        terms::first().apply(&terms::tuple(&one, &two)),
+        terms::second().apply(&terms::tuple(&one, &two)),
+        lazy.clone(),
+        omega.clone(),
+    ];
+    println!("{}", lcrs::strategies_agree_on(&corpus, 5000));
+    let program: lcrs::Program = "let two = λf x. f (f x)
+        let succ = λn f x. f (n f x)
+        let three = succ two
+        three"
+        .parse()
+        .unwrap();
+    print!("{}", program);
+    println!();
+    println!(
+        "{}",
+        verdict(program.to_expr().unwrap().equivalence(&3_u32.to_church()))
+    );
+    let shadowed: Expr = "let x = λa. a in x x".parse().unwrap();
+    println!("{}", shadowed);
+    for e in &corpus[..corpus.len() - 1] {
+        print!(
+            "{} ",
+            e.full_reduction().debrujin() == e.debrujin().normalize()
+        );
+    }
+    println!();
+    assert_reduces_to!(terms::add().call(&[2u32, 3u32]), 5);
+    assert_reduces_to!(terms::and().call(&[true, false]), false);
+    assert_reduces_to!(terms::first().apply(&tup), twelve);
+    println!("{}", two.to_string_styled(PrintStyle::UNICODE));
+    println!(
+        "{}",
+        two.to_string_styled(PrintStyle::UNICODE | PrintStyle::COLLAPSE)
+    );
+    println!("{}", terms::add().to_string_styled(PrintStyle::ASCII));
+    println!(
+        "{}",
+        terms::add().to_string_styled(PrintStyle::ASCII | PrintStyle::COLLAPSE)
+    );
+    println!(
+        "{}",
+        terms::add().to_string().parse::<Expr>() == Ok(terms::add())
+    );
+    let digits = [3_u32, 1, 4].iter().rev().fold(terms::nil(), |acc, n| {
+        terms::cons().apply_all_owned(vec![n.to_church(), acc])
+    });
+    println!("{:?}", digits.to_numeral_vec());
+    let mixed = terms::cons().call(&[
+        three.clone(),
+        terms::cons().call(&[terms::t(), terms::nil()]),
+    ]);
+    println!("{:?}", mixed.to_numeral_vec());
+    let three_minus_five = integers::sub_int().call(&[integers::of_int(3), integers::of_int(5)]);
+    println!("{:?}", three_minus_five.to_i32());
+    println!(
+        "{:?}",
+        integers::add_int()
+            .call(&[integers::of_int(-4), integers::of_int(9)])
+            .to_i32()
+    );
+    println!(
+        "{:?}",
+        integers::neg()
+            .apply(&integers::sub_int().call(&[integers::of_int(2), integers::of_int(2)]))
+            .to_i32()
+    );
+    // 25 000 nested applications overflowed the recursive reducer.
+    let chain = (0..25_000).fold(
+        Expr::lambda("z", Expr::var("z")).apply(&Expr::var("y")),
+        |acc, _| Expr::App(Box::new(Expr::var("x")), Box::new(acc)),
+    );
+    println!("{}", chain.reduction().fv().len());
+    println!(
+        "{:?} {:?}",
+        terms::add()
+            .call(&[2_u32, 3])
+            .equivalent_within(&5_u32.to_church(), 100, Strategy::Normal),
+        omega.equivalent_within(&five, 100, Strategy::Normal)
+    );
+    let capturing = Expr::lambda("y", terms::t().apply(&Expr::var("y")));
+    let canonical = capturing.full_reduction_with(&mut FreshSupply::new());
+    let from_threads: Vec<Expr> = std::thread::scope(|s| {
+        let handles: Vec<_> = (0..2)
+            .map(|_| s.spawn(|| capturing.full_reduction_with(&mut FreshSupply::new())))
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    println!(
+        "{canonical} {}",
+        from_threads.iter().all(|e| *e == canonical)
+    );
+    println!(
+        "{:?} {:?} {:?}",
+        three.cmp_numeral(&five),
+        terms::add().call(&[2_u32, 3]).cmp_numeral(&five),
+        three.cmp_numeral(&terms::t())
+    );
+    let highlighted: Expr = "λx. x y".parse().unwrap();
+    let rendered = highlighted.to_string();
+    for (span, kind) in highlighted.to_annotated() {
+        print!("{}:{kind:?} ", &rendered[span]);
+    }
+    println!();
+    let shadowed: Expr = "λy. x (λx. x) x".parse().unwrap();
+    println!(
+        "{} {} {}",
+        shadowed.occurs_free("x"),
+        shadowed.free_occurrence_count("x"),
+        shadowed.occurs_free("y")
+    );
+    println!(
+        "{} {} {}",
+        3_u8.to_church()
+            .alpha_eq(&"λs z. s (s (s z))".parse().unwrap()),
+        2_u64.to_church().alpha_eq(&two),
+        255_u8.to_church().to_numeral() == Ok(255)
+    );
+    println!(
+        "{} {:?}",
+        terms::builtin("succ") == Some(terms::succ()),
+        terms::builtin("frobnicate")
+    );
+    assert_reduces_to!(
+        terms::builtin("S")
+            .unwrap()
+            .call(&[terms::builtin("K").unwrap(), terms::builtin("K").unwrap()]),
+        terms::builtin("I").unwrap()
+    );
+    let stepped: Expr = "(λx. x) ((λy. y) z)".parse().unwrap();
+    let steps: Vec<Expr> = stepped.reduction_steps(Strategy::Normal).collect();
+    println!(
+        "{} {:?} {:?}",
+        steps.len(),
+        steps.last().map(Expr::to_string),
+        Expr::var("z").reduce_once(Strategy::Normal)
+    );
+    // `exp` renames the inner binder to a primed `x`.
+    let renamed = terms::exp().call(&[2_u32, 2]).full_reduction();
+    println!("{:?} {:?}", renamed.bound_variables(), renamed.to_numeral());
+    let swap = [
+        ("x".to_string(), Expr::var("y")),
+        ("y".to_string(), Expr::var("z")),
+    ];
+    let xy = Expr::var("x").apply(&Expr::var("y"));
+    let sequential = swap
+        .iter()
+        .fold(xy.clone(), |acc, (id, e)| acc.substitution(id, e));
+    println!("{} {}", xy.subst_many(&swap), sequential);
+    println!("{}", Expr::lambda("y", xy.clone()).subst_many(&swap));
+    let twice_normalized: Vec<String> = (0..2)
+        .map(|_| terms::exp().call(&[3_u32, 2]).full_reduction().to_string())
+        .collect();
+    println!(
+        "{} {}",
+        twice_normalized[0],
+        twice_normalized[0] == twice_normalized[1]
+    );
+    let const_x: Expr = "λy. x".parse().unwrap();
+    let opened: Expr = "λx. x y (λy. y z)".parse().unwrap();
+    println!(
+        "{} {}",
+        const_x.rename("x", "y"),
+        opened.map_free_vars(|_| Expr::church_zero())
+    );
+    let a = Type::base("a");
+    let assume_x: TypeCtx = vec![("x".to_string(), a.clone())];
+    let apply_f: TypeCtx = vec![
+        ("f".to_string(), Type::arrow(a.clone(), a.clone())),
+        ("y".to_string(), Type::base("b")),
+    ];
+    println!(
+        "{} {:?} {:?}",
+        "λx. x"
+            .parse::<Expr>()
+            .unwrap()
+            .type_check(&assume_x)
+            .unwrap(),
+        "λx. x x".parse::<Expr>().unwrap().type_check(&assume_x),
+        "f y".parse::<Expr>().unwrap().type_check(&apply_f)
+    );
+    println!(
+        "{:?}",
+        two.check_type(
+            &vec![],
+            &Type::arrow(Type::arrow(a.clone(), a.clone()), Type::arrow(a.clone(), a))
+        )
+    );
+    for src in [
+        "λx. x",
+        "λf x. f (f x)",
+        "λf g x. f (g x)",
+        "λx y. x",
+        "λx y. y",
+        "λc z. c (λx y. x) (c (λx y. y) z)",
+    ] {
+        print!("{} | ", src.parse::<Expr>().unwrap().infer().unwrap());
+    }
+    println!();
+    println!("{:?}", "λx. x x".parse::<Expr>().unwrap().infer());
+    assert_reduces_to!(terms::succ().compose(&terms::succ()).apply(&three), 5);
+    assert_reduces_to!(terms::succ().twice().twice().apply(&one), 5);
+    let add_x = terms::add().apply(&Expr::var("x"));
+    println!("{}", add_x.compose(&terms::succ()));
+    let trace = terms::succ().apply(&zero).trace_json(100, Strategy::Normal);
+    println!(
+        "{} {} {:?}",
+        trace.len(),
+        trace.windows(2).all(|w| w[0].after == w[1].before),
+        trace.last().map(|entry| (&entry.after, entry.normal))
+    );
+    println!(
+        "{:?} {:?} {:?}",
+        omega.full_reduction_bounded_checked(50),
+        omega3.full_reduction_bounded_checked(8),
+        onep.full_reduction_bounded_checked(50)
+            .map(|e| e.to_string())
+    );
+    struct Free(Vec<String>);
+    impl lcrs::Visitor for Free {
+        type Output = Vec<String>;
+        fn visit_lam(&mut self, id: &String, mut body: Vec<String>) -> Vec<String> {
+            body.retain(|x| x != id);
+            body
+        }
+        fn visit_app(&mut self, mut m: Vec<String>, mut n: Vec<String>) -> Vec<String> {
+            m.append(&mut n);
+            m
+        }
+        fn visit_var(&mut self, id: &String) -> Vec<String> {
+            self.0.push(id.clone());
+            vec![id.clone()]
+        }
+    }
+    let open = "λx. x y (λy. y z)".parse::<Expr>().unwrap();
+    let mut free = Free(vec![]);
+    println!(
+        "{:?} {:?} {} {}",
+        open.fold(&mut free),
+        free.0,
+        open.size(),
+        open.depth()
+    );
     println!(
         "{}",
-        lcterms::first().apply(&tup).full_reduction().to_numeral()
+        open.cata(
+            |id, b| format!("(λ{id}. {b})"),
+            |m, n| format!("({m} {n})"),
+            |id| id.clone()
+        )
+    );
+    let big = terms::exp().call(&[2_u32, 10]);
+    let steps = |s| big.reduction_steps(s).take(1_000).count();
+    println!(
+        "{} {} {:?}",
+        steps(Strategy::Normal),
+        steps(Strategy::NumeralAware),
+        terms::add()
+            .call(&[terms::mul().call(&[3_u32, 4]), 5_u32.to_church()])
+            .reduce_with(Strategy::NumeralAware, 3)
+            .map(|nf| nf.to_numeral())
+    );
+    for e in [terms::nil(), terms::t(), three.clone(), omega.clone()] {
+        print!(
+            "{} {} {} | ",
+            e.is_church_numeral(),
+            e.is_church_bool(),
+            e.is_church_list()
+        );
+    }
+    println!();
+    let left = "λx y. x (y z)".parse::<Expr>().unwrap();
+    let right = "λx w. x (λv. v) z".parse::<Expr>().unwrap();
+    for node in left.diff(&right) {
+        println!("{node:?}");
+    }
+    println!(
+        "{:?} {}",
+        "λx. x"
+            .parse::<Expr>()
+            .unwrap()
+            .diff(&"λy. y".parse().unwrap())
+            .len(),
+        "λx. x"
+            .parse::<Expr>()
+            .unwrap()
+            .alpha_diff(&"λy. y".parse().unwrap())
+            .is_empty()
+    );
+    let swapped = "λp f. p (λa b. f b a)".parse::<Expr>().unwrap();
+    println!(
+        "{:?}",
+        swapped
+            .apply(&terms::tuple(&three, &Expr::var("z")))
+            .to_pair()
+            .map(|(a, b)| format!("{a} {b}"))
     );
+    let app = "f (g x) y".parse::<Expr>().unwrap();
+    let (head, args) = app.to_app_vec();
+    println!(
+        "{head} | {} {:?}",
+        args.iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>()
+            .join(" | "),
+        "λf x. f f x".parse::<Expr>().unwrap().to_numeral()
+    );
+    println!(
+        "{:?} {:?} {:?}",
+        terms::mul().call(&[6_u32, 7]).reduce_to_numeral(10_000),
+        terms::lt().call(&[2_u32, 3]).reduce_to_bool(10_000),
+        omega.reduce_to_numeral(100)
+    );
+    let mut interner = lcrs::Interner::new();
+    let ids: Vec<_> = (0..4_u32)
+        .flat_map(|n| [n.to_church(), n.to_church().to_canonical()])
+        .map(|e| interner.intern(&e))
+        .collect();
+    println!(
+        "{} {} {}",
+        ids.len(),
+        interner.len(),
+        interner.resolve(ids[5])
+    );
+    let eta_succ = "λn f x. f (n (λy. f y) x)";
+    println!(
+        "{} {} {}",
+        "λx y. g x y".parse::<Expr>().unwrap().eta_reduce(),
+        "λx. f x".parse::<Expr>().unwrap().eta_eq(&Expr::var("f")),
+        eta_succ.parse::<Expr>().unwrap().eta_eq(&terms::succ())
+    );
+    let ski = lcrs::combinatory::to_ski(&terms::combinators::c());
+    println!(
+        "{} => {} {}",
+        ski,
+        ski.clone()
+            .app(lcrs::combinatory::CL::Var("f".to_string()))
+            .app(lcrs::combinatory::CL::Var("a".to_string()))
+            .app(lcrs::combinatory::CL::Var("b".to_string()))
+            .normalize(),
+        ski.to_lambda()
+            .full_reduction()
+            .alpha_eq(&terms::combinators::c())
+    );
+    println!(
+        "{:?} {:?}",
+        terms::div().call(&[17_u32, 5]).reduce_to_numeral(10_000),
+        terms::mod_().call(&[17_u32, 5]).reduce_to_numeral(10_000)
+    );
+    println!(
+        "{:?}",
+        "λx. λy. x z"
+            .parse::<Expr>()
+            .unwrap()
+            .capture_sites("z", &Expr::var("y"))
+    );
+    let greeting = terms::cons()
+        .apply(&104_u32.to_church())
+        .apply(&Expr::church_of_str("ello").unwrap());
+    println!("{:?}", greeting.to_church_string());
+    println!(
+        "{:?}",
+        "mul 6 7"
+            .parse::<Expr>()
+            .unwrap()
+            .substitution(&"mul".to_string(), &terms::mul())
+            .reduce_to_numeral(10_000)
+    );
+    println!(
+        "{:?}",
+        "(λx. x x) (λy. f (y y))"
+            .parse::<Expr>()
+            .unwrap()
+            .reduce_with_depth(Strategy::Normal, 1_000_000, 100)
+    );
+    let digits = Expr::church_of_str("abc").unwrap();
+    println!(
+        "{:?} {:?}",
+        terms::map()
+            .call(&[terms::succ(), digits.clone()])
+            .to_church_string(),
+        terms::length().apply(&digits).reduce_to_numeral(10_000)
+    );
+    let cfg = lcrs::EvalConfig::default().detect_cycles(true);
+    println!(
+        "{:?} {:?}",
+        omega.normalize_with(&cfg),
+        terms::add()
+            .call(&[2_u32, 2])
+            .normalize_with(&cfg)
+            .map(|nf| nf.to_string())
+    );
+    println!("{}", "λx y x".parse::<Expr>().unwrap_err());
+    let mut contractions = 0;
+    let power = terms::exp().call(&[2_u32, 5]);
+    println!(
+        "{} {} vs {}",
+        power
+            .normalize_lazy_with(|_| contractions += 1)
+            .to_numeral()
+            .unwrap(),
+        contractions,
+        power.reduction_steps(Strategy::Normal).count()
+    );
+    println!(
+        "{:?}",
+        terms::pred().find_subterm(&"λu. u".parse::<Expr>().unwrap())
+    );
+    let id: Expr = "λx. x".parse().unwrap();
     println!(
         "{}",
-        lcterms::second().apply(&tup).full_reduction().to_numeral()
+        terms::pred().rewrite(&id, &"λy. y y".parse().unwrap())
+    );
+    let eta = "λ?x. ?f ?x"
+        .parse::<lcrs::Pattern>()
+        .unwrap()
+        .not_free_in("x", "f");
+    for src in ["λx. g x", "λx. x x"] {
+        let bindings = src.parse::<Expr>().unwrap().match_pattern(&eta);
+        println!(
+            "{}: {:?}",
+            src,
+            bindings.map(|b| b.get("f").unwrap().to_string())
+        );
+    }
+    let (_, church_steps) = terms::pred()
+        .apply(&8_u32.to_church())
+        .normalize_counted(1_000)
+        .unwrap();
+    let (_, scott_steps) = scott::pred()
+        .apply(&scott::nat(8))
+        .normalize_counted(1_000)
+        .unwrap();
+    println!(
+        "pred 8: church {} steps, scott {}",
+        church_steps, scott_steps
+    );
+    let program = lcrs::builder::term()
+        .num(5)
+        .add(lcrs::builder::term().num(7))
+        .build();
+    println!(
+        "{} = {}",
+        program,
+        program.reduce_to_numeral(1_000).unwrap()
+    );
+    println!("{}", terms::add().to_latex());
+    let product: terms::ChurchExpr = (1..=4).map(terms::Church).product();
+    println!("4! = {}", product.0.reduce_to_numeral(10_000).unwrap());
+    let mut deepest = 0;
+    terms::mul()
+        .call(&[3_u32, 4])
+        .normalize_observed(&lcrs::EvalConfig::default(), &mut |_, path| {
+            deepest = deepest.max(path.len())
+        })
+        .unwrap();
+    println!("deepest redex: {}", deepest);
+    let fast = terms::church_fast(1000);
+    let cfg = lcrs::EvalConfig::default().numeral_aware(true);
+    let (_, steps) = fast.normalize_counted(100_000).unwrap();
+    let mut arithmetic = 0;
+    let value = fast
+        .normalize_observed(&cfg, &mut |_, _| arithmetic += 1)
+        .unwrap();
+    println!(
+        "church_fast(1000): {} in {} steps, {} numeral-aware",
+        value.to_numeral().unwrap(),
+        steps,
+        arithmetic
     );
 }
@@ -0,0 +1,89 @@
+use super::*;
+
+// A bottom-up traversal: every node is visited after its children, with the
+// values computed for them. Binders are passed by name only; a visitor that
+// needs scoping keeps its own.
+pub trait Visitor {
+    type Output;
+
+    fn visit_lam(&mut self, id: &Id, body: Self::Output) -> Self::Output;
+    fn visit_app(&mut self, m: Self::Output, n: Self::Output) -> Self::Output;
+    fn visit_var(&mut self, id: &Id) -> Self::Output;
+}
+
+enum Step<'a> {
+    Visit(&'a Expr),
+    Lam(&'a Id),
+    App,
+}
+
+struct Cata<L, A, V> {
+    lam: L,
+    app: A,
+    var: V,
+}
+
+impl<T, L, A, V> Visitor for Cata<L, A, V>
+where
+    L: Fn(&Id, T) -> T,
+    A: Fn(T, T) -> T,
+    V: Fn(&Id) -> T,
+{
+    type Output = T;
+
+    fn visit_lam(&mut self, id: &Id, body: T) -> T {
+        (self.lam)(id, body)
+    }
+
+    fn visit_app(&mut self, m: T, n: T) -> T {
+        (self.app)(m, n)
+    }
+
+    fn visit_var(&mut self, id: &Id) -> T {
+        (self.var)(id)
+    }
+}
+
+impl Expr {
+    // Runs `v` over the term, left to right. Uses an explicit stack, so deep
+    // terms do not overflow.
+    pub fn fold<V: Visitor>(&self, v: &mut V) -> V::Output {
+        let mut todo = vec![Step::Visit(self)];
+        let mut done = vec![];
+        while let Some(step) = todo.pop() {
+            match step {
+                Step::Visit(Expr::Lam(id, body)) => {
+                    todo.push(Step::Lam(id));
+                    todo.push(Step::Visit(body));
+                }
+                Step::Visit(Expr::App(m, n)) => {
+                    todo.push(Step::App);
+                    todo.push(Step::Visit(n));
+                    todo.push(Step::Visit(m));
+                }
+                Step::Visit(Expr::Var(id)) => done.push(v.visit_var(id)),
+                Step::Lam(id) => {
+                    let body = done.pop().unwrap();
+                    done.push(v.visit_lam(id, body));
+                }
+                Step::App => {
+                    let n = done.pop().unwrap();
+                    let m = done.pop().unwrap();
+                    done.push(v.visit_app(m, n));
+                }
+            }
+        }
+        done.pop().unwrap()
+    }
+
+    // `fold` with one closure per constructor, e.g.
+    // `e.cata(|_, b| 1 + b, |m, n| 1 + m + n, |_| 1)` counts the nodes.
+    pub fn cata<T>(
+        &self,
+        lam: impl Fn(&Id, T) -> T,
+        app: impl Fn(T, T) -> T,
+        var: impl Fn(&Id) -> T,
+    ) -> T {
+        self.fold(&mut Cata { lam, app, var })
+    }
+}
@@ -0,0 +1,57 @@
+// Browser entry points. Every function takes source text and returns a JSON
+// string, `{"error": {"message": ...}}` when something goes wrong (with the
+// byte `offset` for parse errors), so nothing panics across the FFI
+// boundary. The `*_json` functions do the work and can be called from Rust as
+// well.
+
+use super::*;
+use serde_json::{json, Value};
+use wasm_bindgen::prelude::*;
+
+fn error(err: &ParseError) -> Value {
+    json!({ "error": err })
+}
+
+// `{"term": <Expr>, "text": "<Display>"}` for the parsed term.
+pub fn parse_json(src: &str) -> String {
+    let value = match src.parse::<Expr>() {
+        Ok(expr) => json!({ "term": expr, "text": expr.to_string() }),
+        Err(err) => error(&err),
+    };
+    value.to_string()
+}
+
+// The normal-order normal form, like `parse_json`, or an error if there
+// is none within `max_steps` contractions.
+pub fn normalize_json(src: &str, max_steps: u32) -> String {
+    let value = match src.parse::<Expr>() {
+        Ok(expr) => match expr.reduce_with(Strategy::Normal, max_steps as usize) {
+            Some(nf) => json!({ "term": nf, "text": nf.to_string() }),
+            None => json!({ "error": {
+                "message": format!("no normal form within {max_steps} steps"),
+            } }),
+        },
+        Err(err) => error(&err),
+    };
+    value.to_string()
+}
+
+#[wasm_bindgen]
+pub fn parse(src: &str) -> JsValue {
+    JsValue::from_str(&parse_json(src))
+}
+
+#[wasm_bindgen]
+pub fn normalize(src: &str, max_steps: u32) -> JsValue {
+    JsValue::from_str(&normalize_json(src, max_steps))
+}
+
+// The numeral `src` normalizes to, if it does within the default budget.
+#[wasm_bindgen]
+pub fn to_numeral_str(src: &str) -> Option<u32> {
+    let expr = src.parse::<Expr>().ok()?;
+    expr.normalize_with(&EvalConfig::default())
+        .ok()?
+        .to_numeral()
+        .ok()
+}